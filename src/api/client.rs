@@ -1,12 +1,98 @@
+use reqwest::StatusCode;
 use reqwest::blocking::{Client, Response};
-use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::header::{
+    ACCEPT, AUTHORIZATION, CONTENT_TYPE, ETAG, HeaderMap, HeaderValue, IF_MATCH,
+};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use super::error::ApiError;
 
 const DEFAULT_BASE_URL: &str = "https://api.builtfast.com";
 const USER_AGENT: &str = concat!("vector-cli/", env!("CARGO_PKG_VERSION"));
+const MASK: &str = "********";
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+static RECORDED: AtomicUsize = AtomicUsize::new(0);
+
+/// Records mutating requests on stderr instead of sending them. GET
+/// requests are still sent.
+pub fn init_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// How many requests a dry run has recorded instead of sending.
+pub fn recorded_requests() -> usize {
+    RECORDED.load(Ordering::Relaxed)
+}
+
+/// Whether a request body field holds a secret that must not be printed.
+/// `value` is only secret on the secrets endpoints; elsewhere (e.g. WAF
+/// rules) it is a plain match value.
+fn is_secret_field(name: &str, secrets_endpoint: bool) -> bool {
+    let name = name.to_ascii_lowercase();
+    (secrets_endpoint && name == "value")
+        || ["password", "secret", "token", "private_key"]
+            .iter()
+            .any(|s| name.contains(s))
+}
+
+/// Replaces string values of secret fields, at any depth, with a mask.
+fn mask_secrets(value: &mut Value, secrets_endpoint: bool) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if field.is_string() && is_secret_field(key, secrets_endpoint) {
+                    *field = json!(MASK);
+                } else {
+                    mask_secrets(field, secrets_endpoint);
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| mask_secrets(item, secrets_endpoint)),
+        _ => {}
+    }
+}
+
+/// Describes a request that `--dry-run` kept from being sent.
+fn describe_request(
+    method: &str,
+    path: &str,
+    if_match: Option<&str>,
+    body: Option<Value>,
+    file: Option<&Path>,
+) -> String {
+    let (path, query) = match path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path, None),
+    };
+    let mut lines = vec![format!("DRY RUN: {} {}", method, path)];
+    if let Some(query) = query {
+        lines.push(format!("  Query: {}", query));
+    }
+    if let Some(etag) = if_match {
+        lines.push(format!("  If-Match: {}", etag));
+    }
+    if let Some(file) = file {
+        let size = std::fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+        lines.push(format!("  File: {} ({} bytes)", file.display(), size));
+    }
+    if let Some(mut body) = body {
+        mask_secrets(&mut body, path.contains("secrets"));
+        let body = serde_json::to_string_pretty(&body).unwrap_or_default();
+        lines.extend(body.lines().map(|line| format!("  {}", line)));
+    }
+    lines.join("\n")
+}
 
 pub struct ApiClient {
     client: Client,
@@ -49,6 +135,27 @@ impl ApiClient {
         Ok(headers)
     }
 
+    /// Prints a mutating request instead of sending it, and answers with an
+    /// empty `data` object.
+    fn record<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        if_match: Option<&str>,
+        body: Option<Value>,
+        file: Option<&Path>,
+    ) -> Result<T, ApiError> {
+        eprintln!("{}", describe_request(method, path, if_match, body, file));
+        RECORDED.fetch_add(1, Ordering::Relaxed);
+        serde_json::from_value(json!({"data": {}}))
+            .map_err(|e| ApiError::Other(format!("JSON parse error: {}", e)))
+    }
+
+    fn body_value<B: Serialize>(body: &B) -> Result<Value, ApiError> {
+        serde_json::to_value(body)
+            .map_err(|e| ApiError::Other(format!("Failed to serialize request: {}", e)))
+    }
+
     fn handle_response<T: DeserializeOwned>(&self, response: Response) -> Result<T, ApiError> {
         let status = response.status();
         let body = response.text().map_err(ApiError::NetworkError)?;
@@ -73,6 +180,27 @@ impl ApiClient {
         self.handle_response(response)
     }
 
+    /// Like `get`, also returning the ETag header when the API sends one.
+    pub fn get_with_etag<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<(T, Option<String>), ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.headers()?)
+            .send()
+            .map_err(ApiError::NetworkError)?;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+
+        Ok((self.handle_response(response)?, etag))
+    }
+
     pub fn get_with_query<T: DeserializeOwned, Q: Serialize>(
         &self,
         path: &str,
@@ -95,6 +223,9 @@ impl ApiClient {
         path: &str,
         body: &B,
     ) -> Result<T, ApiError> {
+        if is_dry_run() {
+            return self.record("POST", path, None, Some(Self::body_value(body)?), None);
+        }
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .client
@@ -108,6 +239,9 @@ impl ApiClient {
     }
 
     pub fn post_empty<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        if is_dry_run() {
+            return self.record("POST", path, None, None, None);
+        }
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .client
@@ -124,19 +258,51 @@ impl ApiClient {
         path: &str,
         body: &B,
     ) -> Result<T, ApiError> {
+        self.put_if_match(path, body, None)
+    }
+
+    /// Like `put`, but with `If-Match: etag` so the API rejects the update
+    /// with 412 Precondition Failed, reported as `ApiError::Conflict`, when
+    /// the resource changed since `etag` was read.
+    pub fn put_if_match<T: DeserializeOwned, B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+        etag: Option<&str>,
+    ) -> Result<T, ApiError> {
+        if is_dry_run() {
+            return self.record("PUT", path, etag, Some(Self::body_value(body)?), None);
+        }
+        let mut headers = self.headers()?;
+        if let Some(etag) = etag {
+            headers.insert(
+                IF_MATCH,
+                HeaderValue::from_str(etag).map_err(|e| ApiError::Other(e.to_string()))?,
+            );
+        }
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .client
             .put(&url)
-            .headers(self.headers()?)
+            .headers(headers)
             .json(body)
             .send()
             .map_err(ApiError::NetworkError)?;
 
-        self.handle_response(response)
+        let precondition_failed =
+            etag.is_some() && response.status() == StatusCode::PRECONDITION_FAILED;
+        match self.handle_response(response) {
+            Err(ApiError::Other(message)) if precondition_failed => {
+                Err(ApiError::Conflict(message))
+            }
+            result => result,
+        }
     }
 
     pub fn put_empty<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        if is_dry_run() {
+            return self.record("PUT", path, None, None, None);
+        }
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .client
@@ -149,6 +315,9 @@ impl ApiClient {
     }
 
     pub fn delete<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        if is_dry_run() {
+            return self.record("DELETE", path, None, None, None);
+        }
         let url = format!("{}{}", self.base_url, path);
         let response = self
             .client
@@ -163,8 +332,11 @@ impl ApiClient {
     pub fn post_file<T: DeserializeOwned>(
         &self,
         path: &str,
-        file_path: &std::path::Path,
+        file_path: &Path,
     ) -> Result<T, ApiError> {
+        if is_dry_run() {
+            return self.record("POST", path, None, None, Some(file_path));
+        }
         use reqwest::blocking::multipart::{Form, Part};
         use std::fs::File;
         use std::io::Read;
@@ -204,3 +376,50 @@ impl ApiClient {
         self.handle_response(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_secrets() {
+        let mut body = json!({
+            "key": "API_TOKEN",
+            "value": "t0k",
+            "is_secret": true,
+            "secrets": [{"key": "A", "value": "b"}],
+            "db_password": "pw",
+            "name": "site",
+        });
+        let mut rule = json!({"value": "/wp-login.php", "api_token": "t"});
+        mask_secrets(&mut body, true);
+        mask_secrets(&mut rule, false);
+        assert_eq!(rule, json!({"value": "/wp-login.php", "api_token": MASK}));
+        assert_eq!(
+            body,
+            json!({
+                "key": "API_TOKEN",
+                "value": MASK,
+                "is_secret": true,
+                "secrets": [{"key": "A", "value": MASK}],
+                "db_password": MASK,
+                "name": "site",
+            })
+        );
+    }
+
+    #[test]
+    fn test_describe_request() {
+        let described = describe_request(
+            "PUT",
+            "/api/v1/vector/secrets/s1?force=1",
+            Some("\"v2\""),
+            Some(json!({"value": "hunter2"})),
+            None,
+        );
+        assert_eq!(
+            described,
+            "DRY RUN: PUT /api/v1/vector/secrets/s1\n  Query: force=1\n  If-Match: \"v2\"\n  {\n    \"value\": \"********\"\n  }"
+        );
+    }
+}
@@ -8,6 +8,8 @@ pub const EXIT_AUTH_ERROR: i32 = 2;
 pub const EXIT_VALIDATION_ERROR: i32 = 3;
 pub const EXIT_NOT_FOUND: i32 = 4;
 pub const EXIT_NETWORK_ERROR: i32 = 5;
+pub const EXIT_TIMEOUT: i32 = 6;
+pub const EXIT_CONFLICT: i32 = 7;
 
 #[derive(Debug, Error)]
 pub enum ApiError {
@@ -32,6 +34,12 @@ pub enum ApiError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -43,6 +51,8 @@ impl ApiError {
             ApiError::NotFound(_) => EXIT_NOT_FOUND,
             ApiError::ValidationError(_) => EXIT_VALIDATION_ERROR,
             ApiError::ServerError(_) | ApiError::NetworkError(_) => EXIT_NETWORK_ERROR,
+            ApiError::Timeout(_) => EXIT_TIMEOUT,
+            ApiError::Conflict(_) => EXIT_CONFLICT,
             ApiError::ConfigError(_) | ApiError::Other(_) => EXIT_GENERAL_ERROR,
         }
     }
@@ -114,6 +124,8 @@ mod tests {
             EXIT_GENERAL_ERROR
         );
         assert_eq!(ApiError::Other("".into()).exit_code(), EXIT_GENERAL_ERROR);
+        assert_eq!(ApiError::Timeout("".into()).exit_code(), EXIT_TIMEOUT);
+        assert_eq!(ApiError::Conflict("".into()).exit_code(), EXIT_CONFLICT);
     }
 
     #[test]
@@ -130,6 +142,10 @@ mod tests {
             ApiError::from_response(404, "{}"),
             ApiError::NotFound(_)
         ));
+        assert!(matches!(
+            ApiError::from_response(409, "{}"),
+            ApiError::Other(_)
+        ));
         assert!(matches!(
             ApiError::from_response(422, "{}"),
             ApiError::ValidationError(_)
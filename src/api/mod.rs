@@ -1,5 +1,5 @@
 pub mod client;
 pub mod error;
 
-pub use client::ApiClient;
+pub use client::{ApiClient, init_dry_run, is_dry_run, recorded_requests};
 pub use error::{ApiError, EXIT_SUCCESS};
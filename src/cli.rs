@@ -1,6 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::commands::credentials::CredentialFormat;
+use crate::commands::logs::LogExportFormat;
+use crate::output::ColorChoice;
+
 #[derive(Parser)]
 #[command(name = "vector")]
 #[command(about = "CLI for Vector Pro API", long_about = None)]
@@ -14,6 +18,38 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub no_json: bool,
 
+    /// Print only resource IDs (one per line for lists)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// When to use colors in table output
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    pub color: ColorChoice,
+
+    /// Write output to a file, replacing it atomically only on success
+    #[arg(long, global = true, value_name = "PATH")]
+    pub output_file: Option<PathBuf>,
+
+    /// Do not pipe long output through a pager
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Show absolute timestamps instead of relative times in table output
+    #[arg(long, global = true)]
+    pub absolute: bool,
+
+    /// Show absolute timestamps in this time zone (e.g., America/New_York; implies --absolute)
+    #[arg(long, global = true)]
+    pub tz: Option<String>,
+
+    /// Answer yes to confirmation prompts (required for destructive commands when stdin is not a terminal)
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
+    /// Print the POST, PUT and DELETE requests a command would send instead of sending them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -60,6 +96,11 @@ pub enum Commands {
         #[command(subcommand)]
         command: AccountCommands,
     },
+    /// Audit SSH keys across the account and all sites
+    SshKey {
+        #[command(subcommand)]
+        command: SshKeyCommands,
+    },
     /// View events
     Event {
         #[command(subcommand)]
@@ -109,17 +150,32 @@ pub enum SiteCommands {
         /// Site ID
         id: String,
     },
+    /// Show a site with its environments, SSL status and latest deployments
+    Overview {
+        /// Site ID
+        id: String,
+    },
     /// Create a new site
     Create {
         /// Customer ID for the site
-        #[arg(long)]
-        customer_id: String,
+        #[arg(long, required_unless_present = "from")]
+        customer_id: Option<String>,
         /// PHP version for the dev environment
-        #[arg(long)]
-        dev_php_version: String,
+        #[arg(long, required_unless_present = "from")]
+        dev_php_version: Option<String>,
         /// Tags for the site
         #[arg(long)]
         tags: Option<Vec<String>>,
+        /// Create the site, environments, secrets, WAF rules and SSH keys
+        /// described in a YAML blueprint
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["customer_id", "dev_php_version", "tags"])]
+        from: Option<PathBuf>,
+        /// Keep what was created if a blueprint step fails, instead of
+        /// rolling back
+        #[arg(long, requires = "from")]
+        keep_partial: bool,
+        #[command(flatten)]
+        wait: WaitArgs,
     },
     /// Update a site
     Update {
@@ -131,12 +187,15 @@ pub enum SiteCommands {
         /// Tags
         #[arg(long)]
         tags: Option<Vec<String>>,
+        /// Refuse to update if the resource was modified after this time (ISO 8601 or e.g. 1h)
+        #[arg(long, value_name = "TIME")]
+        if_unmodified_since: Option<String>,
     },
     /// Delete a site
     Delete {
         /// Site ID
         id: String,
-        /// Skip confirmation
+        /// Skip confirmation (same as --yes)
         #[arg(long)]
         force: bool,
     },
@@ -153,16 +212,28 @@ pub enum SiteCommands {
         /// Tags for the new site
         #[arg(long)]
         tags: Option<Vec<String>>,
+        #[command(flatten)]
+        wait: WaitArgs,
     },
     /// Suspend a site
     Suspend {
-        /// Site ID
-        id: String,
+        /// Site ID, or - to read IDs from stdin
+        #[arg(required_unless_present_any = ["all_sites", "tag", "customer_id"], conflicts_with_all = ["all_sites", "tag", "customer_id"])]
+        id: Option<String>,
+        #[command(flatten)]
+        select: SiteSelectArgs,
+        #[command(flatten)]
+        wait: WaitArgs,
     },
     /// Unsuspend a site
     Unsuspend {
-        /// Site ID
-        id: String,
+        /// Site ID, or - to read IDs from stdin
+        #[arg(required_unless_present_any = ["all_sites", "tag", "customer_id"], conflicts_with_all = ["all_sites", "tag", "customer_id"])]
+        id: Option<String>,
+        #[command(flatten)]
+        select: SiteSelectArgs,
+        #[command(flatten)]
+        wait: WaitArgs,
     },
     /// Reset SFTP password
     ResetSftpPassword {
@@ -174,53 +245,306 @@ pub enum SiteCommands {
         /// Site ID
         id: String,
     },
-    /// Purge site cache
-    PurgeCache {
+    /// Show development database credentials, or write them to a .env file
+    DbCredentials {
         /// Site ID
         id: String,
+        #[command(flatten)]
+        output: DbCredentialsArgs,
+    },
+    /// Purge site cache
+    PurgeCache {
+        /// Site ID, or - to read IDs from stdin
+        #[arg(required_unless_present_any = ["all_sites", "tag", "customer_id"], conflicts_with_all = ["all_sites", "tag", "customer_id"])]
+        id: Option<String>,
+        #[command(flatten)]
+        select: SiteSelectArgs,
         /// Cache tag to purge
         #[arg(long)]
         cache_tag: Option<String>,
         /// URL to purge
         #[arg(long)]
         url: Option<String>,
+        /// Purge every URL listed in FILE, one per line (- for stdin)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["cache_tag", "url", "sitemap", "all_sites", "tag", "customer_id"])]
+        urls_from: Option<String>,
+        /// Purge every page in a sitemap or sitemap index (URL or file)
+        #[arg(long, value_name = "URL_OR_FILE", conflicts_with_all = ["cache_tag", "url", "all_sites", "tag", "customer_id"])]
+        sitemap: Option<String>,
+        /// URLs to purge at once with --urls-from or --sitemap
+        #[arg(long, default_value = "10", value_parser = clap::value_parser!(u16).range(1..=100))]
+        batch_size: u16,
+        /// Milliseconds to pause between batches
+        #[arg(long, value_name = "MS", default_value = "1000")]
+        batch_delay: u64,
     },
     /// View site logs
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Logs {
+        #[command(subcommand)]
+        command: Option<Box<SiteLogsCommands>>,
         /// Site ID
-        id: String,
-        /// Start time (ISO 8601 format)
-        #[arg(long)]
-        start_time: Option<String>,
-        /// End time (ISO 8601 format)
-        #[arg(long)]
-        end_time: Option<String>,
+        #[arg(required = true)]
+        id: Option<String>,
+        #[command(flatten)]
+        filter: Box<LogFilterArgs>,
         /// Number of log entries
         #[arg(long)]
         limit: Option<u32>,
-        /// Environment name to filter
-        #[arg(long)]
-        environment: Option<String>,
-        /// Deployment ID to filter
-        #[arg(long)]
-        deployment_id: Option<String>,
-        /// Log level to filter (e.g., error, warning, info)
-        #[arg(long)]
-        level: Option<String>,
         /// Pagination cursor from previous response
         #[arg(long)]
         cursor: Option<String>,
+        /// Keep polling for new log entries until interrupted
+        #[arg(long, short)]
+        follow: bool,
+        /// Seconds between polls in follow mode
+        #[arg(long, default_value = "5", requires = "follow", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+        /// Columns to show, by field name (comma-separated)
+        #[arg(long, value_delimiter = ',', conflicts_with = "raw")]
+        fields: Option<Vec<String>>,
+        /// Print rows exactly as returned, one JSON value per line
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Wait for a site to reach a status
+    Wait {
+        /// Site ID
+        id: String,
+        /// Status to wait for
+        #[arg(long, default_value = "active")]
+        status: String,
+        /// Seconds to wait before giving up
+        #[arg(long, default_value = "600")]
+        timeout: u64,
+        /// Seconds between status checks
+        #[arg(long, default_value = "5", value_parser = clap::value_parser!(u64).range(1..))]
+        poll_interval: u64,
     },
     /// Regenerate wp-config.php
     WpReconfig {
+        /// Site ID, or - to read IDs from stdin
+        #[arg(required_unless_present_any = ["all_sites", "tag", "customer_id"], conflicts_with_all = ["all_sites", "tag", "customer_id"])]
+        id: Option<String>,
+        #[command(flatten)]
+        select: SiteSelectArgs,
+        #[command(flatten)]
+        wait: WaitArgs,
+    },
+    /// Open an SSH session on a site
+    Ssh {
+        /// Site ID
+        id: String,
+        /// Extra arguments for ssh, such as a remote command (after --)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Open an SFTP session on a site
+    Sftp {
         /// Site ID
         id: String,
     },
+    /// Add or update Host entries for sites in ~/.ssh/config
+    SshConfig {
+        /// Site IDs
+        #[arg(required_unless_present_any = ["all_sites", "tag", "customer_id"], conflicts_with_all = ["all_sites", "tag", "customer_id"])]
+        ids: Vec<String>,
+        #[command(flatten)]
+        select: SiteSelectArgs,
+        /// SSH config file to update
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+        /// Print the Host entries instead of writing them
+        #[arg(long, conflicts_with = "file")]
+        print: bool,
+    },
     /// Manage site SSH keys
     SshKey {
         #[command(subcommand)]
         command: SiteSshKeyCommands,
     },
+    /// Add, remove or replace site tags
+    Tag {
+        #[command(subcommand)]
+        command: TagCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TagCommands {
+    /// Add tags, keeping the existing ones
+    Add {
+        #[command(flatten)]
+        args: TagArgs,
+    },
+    /// Remove tags
+    Remove {
+        #[command(flatten)]
+        args: TagArgs,
+    },
+    /// Replace all tags
+    Set {
+        #[command(flatten)]
+        args: TagArgs,
+        /// Remove all tags instead of replacing them (asks for confirmation)
+        #[arg(long, conflicts_with = "tags")]
+        clear: bool,
+    },
+}
+
+/// The resources to tag and the tags, shared by sites and environments.
+#[derive(Args)]
+pub struct TagArgs {
+    /// ID (comma-separated for several); leave out with --matching-tag
+    #[arg(required_unless_present = "matching_tag")]
+    pub id: Option<String>,
+    /// Tags
+    pub tags: Vec<String>,
+    /// Apply to everything that already has this tag
+    #[arg(long, value_name = "TAG")]
+    pub matching_tag: Option<String>,
+}
+
+/// Selects many sites for a bulk operation instead of a single ID.
+#[derive(Args)]
+pub struct SiteSelectArgs {
+    /// Apply to every site on the account
+    #[arg(long, conflicts_with_all = ["tag", "customer_id"])]
+    pub all_sites: bool,
+    /// Apply to sites with this tag
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+    /// Apply to sites with this customer ID
+    #[arg(long, value_name = "ID")]
+    pub customer_id: Option<String>,
+    /// Number of sites to process at once in bulk operations
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u16).range(1..=32))]
+    pub concurrency: u16,
+}
+
+/// Where to read an SSH public key from. Without any of these, the first
+/// of ~/.ssh/id_ed25519.pub, id_ecdsa.pub and id_rsa.pub is used.
+#[derive(Args)]
+pub struct PublicKeyArgs {
+    /// Key name (defaults to the key comment)
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Public key content
+    #[arg(long, conflicts_with_all = ["public_key_file", "from_agent"])]
+    pub public_key: Option<String>,
+    /// Read the public key from a file
+    #[arg(long, value_name = "PATH", conflicts_with = "from_agent")]
+    pub public_key_file: Option<PathBuf>,
+    /// Use a key loaded in ssh-agent, matched by fingerprint or comment if
+    /// several are loaded
+    #[arg(long, value_name = "MATCH", num_args = 0..=1, default_missing_value = "")]
+    pub from_agent: Option<String>,
+}
+
+/// How to print or save database credentials.
+#[derive(Args)]
+pub struct DbCredentialsArgs {
+    /// Print in this format instead of a table
+    #[arg(long, value_enum)]
+    pub format: Option<CredentialFormat>,
+    /// Reset the password first; the API only returns it on reset
+    #[arg(long)]
+    pub reset_password: bool,
+    /// Replace the DB_* keys in this .env file, leaving other lines as they are
+    #[arg(long, value_name = "PATH", conflicts_with = "format")]
+    pub env_file: Option<PathBuf>,
+}
+
+/// Options for site commands that start work in the background.
+#[derive(Args)]
+pub struct WaitArgs {
+    /// Wait for the site to settle before exiting
+    #[arg(long)]
+    pub wait: bool,
+    /// Seconds to wait before giving up
+    #[arg(long, default_value = "600", requires = "wait")]
+    pub timeout: u64,
+    /// Seconds between status checks
+    #[arg(long, default_value = "5", requires = "wait", value_parser = clap::value_parser!(u64).range(1..))]
+    pub poll_interval: u64,
+}
+
+/// Filters shared by the `site logs` commands.
+#[derive(Args)]
+pub struct LogFilterArgs {
+    /// Start time (ISO 8601, local datetime, or relative like 15m, 2h, yesterday)
+    #[arg(long)]
+    pub start_time: Option<String>,
+    /// End time (ISO 8601, local datetime, or relative like 15m, now-1d)
+    #[arg(long)]
+    pub end_time: Option<String>,
+    /// Environment name to filter
+    #[arg(long)]
+    pub environment: Option<String>,
+    /// Deployment ID to filter
+    #[arg(long)]
+    pub deployment_id: Option<String>,
+    /// Log level to filter (e.g., error, warning, info)
+    #[arg(long)]
+    pub level: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum SiteLogsCommands {
+    /// Export every log entry in a time range to a file
+    Export {
+        /// Site ID
+        id: String,
+        #[command(flatten)]
+        filter: LogFilterArgs,
+        /// File to write (a .gz suffix enables compression)
+        #[arg(long, value_name = "PATH")]
+        output: PathBuf,
+        /// File format (defaults to csv for .csv files, ndjson otherwise)
+        #[arg(long, value_enum)]
+        format: Option<LogExportFormat>,
+        /// Gzip-compress the output
+        #[arg(long)]
+        gzip: bool,
+        /// Log entries per request
+        #[arg(long)]
+        limit: Option<u32>,
+        /// Start from this pagination cursor
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Continue an interrupted export with its saved filters and cursor
+        #[arg(long, conflicts_with_all = ["cursor", "start_time", "end_time", "environment", "deployment_id", "level"])]
+        resume: bool,
+    },
+    /// Search log entries across pages, optionally counting them by a field
+    #[command(group(ArgGroup::new("query").args(["grep", "count_by"]).required(true).multiple(true)))]
+    Search {
+        /// Site ID
+        id: String,
+        #[command(flatten)]
+        filter: LogFilterArgs,
+        /// Regular expression matched against every field of each entry
+        #[arg(long, value_name = "PATTERN")]
+        grep: Option<String>,
+        /// Match the pattern case-insensitively
+        #[arg(long, short = 'i', requires = "grep")]
+        ignore_case: bool,
+        /// Count matching entries by the value of this field
+        #[arg(long, value_name = "FIELD")]
+        count_by: Option<String>,
+        /// Number of values to show with --count-by
+        #[arg(long, default_value = "20", requires = "count_by")]
+        top: usize,
+        /// Columns to show for matching entries, by field name (comma-separated)
+        #[arg(long, value_delimiter = ',', conflicts_with = "count_by")]
+        fields: Option<Vec<String>>,
+        /// Stop after fetching this many pages
+        #[arg(long)]
+        max_pages: Option<u32>,
+        /// Log entries per request
+        #[arg(long)]
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -240,12 +564,8 @@ pub enum SiteSshKeyCommands {
     Add {
         /// Site ID
         site_id: String,
-        /// Key name
-        #[arg(long)]
-        name: String,
-        /// Public key content
-        #[arg(long)]
-        public_key: String,
+        #[command(flatten)]
+        key: PublicKeyArgs,
     },
     /// Remove an SSH key from a site
     Remove {
@@ -307,22 +627,40 @@ pub enum EnvCommands {
         /// Tags
         #[arg(long)]
         tags: Option<Vec<String>>,
+        /// Refuse to update if the resource was modified after this time (ISO 8601 or e.g. 1h)
+        #[arg(long, value_name = "TIME")]
+        if_unmodified_since: Option<String>,
     },
     /// Delete an environment
     Delete {
         /// Environment ID
         env_id: String,
+        /// Do not ask for the environment name if it is production
+        #[arg(long)]
+        confirm_production: bool,
     },
     /// Reset environment database password
     ResetDbPassword {
         /// Environment ID
         env_id: String,
     },
+    /// Show environment database credentials, or write them to a .env file
+    DbCredentials {
+        /// Environment ID
+        env_id: String,
+        #[command(flatten)]
+        output: DbCredentialsArgs,
+    },
     /// Manage environment secrets
     Secret {
         #[command(subcommand)]
         command: EnvSecretCommands,
     },
+    /// Add, remove or replace environment tags
+    Tag {
+        #[command(subcommand)]
+        command: TagCommands,
+    },
     /// Manage environment database
     Db {
         #[command(subcommand)]
@@ -375,6 +713,9 @@ pub enum EnvSecretCommands {
         /// Store as a plain environment variable instead of a secret
         #[arg(long)]
         no_secret: bool,
+        /// Refuse to update if the resource was modified after this time (ISO 8601 or e.g. 1h)
+        #[arg(long, value_name = "TIME")]
+        if_unmodified_since: Option<String>,
     },
     /// Delete a secret
     Delete {
@@ -403,6 +744,9 @@ pub enum EnvDbCommands {
         /// Replace string for search-and-replace during import
         #[arg(long)]
         search_replace_to: Option<String>,
+        /// Do not ask for the environment name if it is production
+        #[arg(long)]
+        confirm_production: bool,
     },
     /// Manage import sessions for large files
     ImportSession {
@@ -419,6 +763,9 @@ pub enum EnvDbCommands {
         /// Disable foreign key checks during promote
         #[arg(long)]
         disable_foreign_keys: bool,
+        /// Do not ask for the environment name if it is production
+        #[arg(long)]
+        confirm_production: bool,
     },
     /// Check promote status
     PromoteStatus {
@@ -498,6 +845,9 @@ pub enum DeployCommands {
         /// Include database in the deployment
         #[arg(long)]
         include_database: bool,
+        /// Do not ask for the environment name if it is production
+        #[arg(long)]
+        confirm_production: bool,
     },
     /// Rollback to a previous deployment
     Rollback {
@@ -506,6 +856,9 @@ pub enum DeployCommands {
         /// Target deployment ID to rollback to
         #[arg(long)]
         target_deployment_id: Option<String>,
+        /// Do not ask for the environment name if it is production
+        #[arg(long)]
+        confirm_production: bool,
     },
 }
 
@@ -722,6 +1075,9 @@ pub enum WafRateLimitCommands {
         /// Transformations to apply
         #[arg(long)]
         transformations: Option<Vec<String>>,
+        /// Refuse to update if the resource was modified after this time (ISO 8601 or e.g. 1h)
+        #[arg(long, value_name = "TIME")]
+        if_unmodified_since: Option<String>,
     },
     /// Delete a rate limit rule
     Delete {
@@ -822,6 +1178,19 @@ pub enum AccountCommands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum SshKeyCommands {
+    /// Group account and site keys by fingerprint and show where each is installed
+    Audit {
+        /// Remove the key with this SHA256 fingerprint from the account and every site
+        #[arg(long, value_name = "FINGERPRINT")]
+        remove_fingerprint: Option<String>,
+        /// Skip confirmation (same as --yes)
+        #[arg(long, requires = "remove_fingerprint")]
+        force: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum AccountSshKeyCommands {
     /// List account SSH keys
@@ -840,12 +1209,8 @@ pub enum AccountSshKeyCommands {
     },
     /// Create an SSH key
     Create {
-        /// Key name
-        #[arg(long)]
-        name: String,
-        /// Public key content
-        #[arg(long)]
-        public_key: String,
+        #[command(flatten)]
+        key: PublicKeyArgs,
     },
     /// Delete an SSH key
     Delete {
@@ -937,10 +1302,10 @@ pub enum AccountSecretCommands {
 pub enum EventCommands {
     /// List events
     List {
-        /// Start date (ISO 8601 format)
+        /// Start date (ISO 8601, local datetime, or relative like 2h, yesterday)
         #[arg(long)]
         from: Option<String>,
-        /// End date (ISO 8601 format)
+        /// End date (ISO 8601, local datetime, or relative like 15m, now)
         #[arg(long)]
         to: Option<String>,
         /// Event type filter
@@ -1005,6 +1370,9 @@ pub enum WebhookCommands {
         /// Enable/disable webhook
         #[arg(long)]
         enabled: Option<bool>,
+        /// Refuse to update if the resource was modified after this time (ISO 8601 or e.g. 1h)
+        #[arg(long, value_name = "TIME")]
+        if_unmodified_since: Option<String>,
     },
     /// Delete a webhook
     Delete {
@@ -2,10 +2,13 @@ use serde::Serialize;
 use serde_json::Value;
 
 use crate::api::{ApiClient, ApiError};
+use crate::commands::site;
+use crate::confirm;
 use crate::output::{
-    OutputFormat, extract_pagination, format_bool, format_option, print_json, print_key_value,
-    print_message, print_pagination, print_table,
+    OutputFormat, extract_pagination, format_bool, format_option, format_timestamp, print_json,
+    print_key_value, print_message, print_pagination, print_table,
 };
+use crate::sshkey;
 
 #[derive(Debug, Serialize)]
 struct PaginationQuery {
@@ -143,8 +146,8 @@ pub fn ssh_key_list(
             vec![
                 k["id"].as_str().unwrap_or("-").to_string(),
                 k["name"].as_str().unwrap_or("-").to_string(),
-                format_option(&k["fingerprint"].as_str().map(String::from)),
-                format_option(&k["created_at"].as_str().map(String::from)),
+                sshkey::display_fingerprint(k),
+                format_timestamp(&k["created_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -175,10 +178,7 @@ pub fn ssh_key_show(
     print_key_value(vec![
         ("ID", key["id"].as_str().unwrap_or("-").to_string()),
         ("Name", key["name"].as_str().unwrap_or("-").to_string()),
-        (
-            "Fingerprint",
-            format_option(&key["fingerprint"].as_str().map(String::from)),
-        ),
+        ("Fingerprint", sshkey::display_fingerprint(key)),
         (
             "Public Key Preview",
             format_option(&key["public_key_preview"].as_str().map(String::from)),
@@ -192,7 +192,7 @@ pub fn ssh_key_show(
         ),
         (
             "Created",
-            format_option(&key["created_at"].as_str().map(String::from)),
+            format_timestamp(&key["created_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -232,6 +232,21 @@ pub fn ssh_key_delete(
     key_id: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(&format!("delete SSH key {}", key_id), || {
+        confirm::fetch_details(
+            client,
+            &format!("/api/v1/vector/ssh-keys/{}", key_id),
+            &[
+                ("ID", "id"),
+                ("Name", "name"),
+                ("Fingerprint", "fingerprint"),
+            ],
+        )
+    })? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!("/api/v1/vector/ssh-keys/{}", key_id))?;
 
     if format == OutputFormat::Json {
@@ -278,8 +293,8 @@ pub fn api_key_list(
                     .unwrap_or("-".to_string()),
                 k["name"].as_str().unwrap_or("-").to_string(),
                 format_abilities(&k["abilities"]),
-                format_option(&k["last_used_at"].as_str().map(String::from)),
-                format_option(&k["expires_at"].as_str().map(String::from)),
+                format_timestamp(&k["last_used_at"].as_str().map(String::from)),
+                format_timestamp(&k["expires_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -323,7 +338,7 @@ pub fn api_key_create(
         ("Abilities", format_abilities(&data["abilities"])),
         (
             "Expires",
-            format_option(&data["expires_at"].as_str().map(String::from)),
+            format_timestamp(&data["expires_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -332,11 +347,44 @@ pub fn api_key_create(
     Ok(())
 }
 
+/// Looks up an API key in the listing, since keys cannot be fetched one
+/// by one, and picks what `api_key_delete` shows before asking.
+fn api_key_details(
+    client: &ApiClient,
+    token_id: &str,
+) -> Result<Vec<(&'static str, String)>, ApiError> {
+    let keys = site::fetch_all_pages(client, "/api/v1/vector/api-keys")?;
+    let key = keys
+        .iter()
+        .find(|k| match &k["id"] {
+            Value::String(id) => id == token_id,
+            Value::Number(id) => id.to_string() == token_id,
+            _ => false,
+        })
+        .ok_or_else(|| ApiError::NotFound(format!("No API key with ID {} was found", token_id)))?;
+    Ok(confirm::details(
+        key,
+        &[
+            ("ID", "id"),
+            ("Name", "name"),
+            ("Abilities", "abilities"),
+            ("Last Used", "last_used_at"),
+        ],
+    ))
+}
+
 pub fn api_key_delete(
     client: &ApiClient,
     token_id: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(&format!("delete API key {}", token_id), || {
+        api_key_details(client, token_id)
+    })? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!("/api/v1/vector/api-keys/{}", token_id))?;
 
     if format == OutputFormat::Json {
@@ -381,7 +429,7 @@ pub fn secret_list(
                 s["key"].as_str().unwrap_or("-").to_string(),
                 format_bool(s["is_secret"].as_bool().unwrap_or(true)),
                 format_option(&s["value"].as_str().map(String::from)),
-                format_option(&s["created_at"].as_str().map(String::from)),
+                format_timestamp(&s["created_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -422,11 +470,11 @@ pub fn secret_show(
         ),
         (
             "Created",
-            format_option(&secret["created_at"].as_str().map(String::from)),
+            format_timestamp(&secret["created_at"].as_str().map(String::from)),
         ),
         (
             "Updated",
-            format_option(&secret["updated_at"].as_str().map(String::from)),
+            format_timestamp(&secret["updated_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -496,6 +544,17 @@ pub fn secret_delete(
     secret_id: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(&format!("delete global secret {}", secret_id), || {
+        confirm::fetch_details(
+            client,
+            &format!("/api/v1/vector/global-secrets/{}", secret_id),
+            &[("ID", "id"), ("Key", "key")],
+        )
+    })? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!("/api/v1/vector/global-secrets/{}", secret_id))?;
 
     if format == OutputFormat::Json {
@@ -0,0 +1,513 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::api::{ApiClient, ApiError, is_dry_run};
+use crate::commands::site::{self, Wait};
+use crate::output::{OutputFormat, print_json, print_message, print_table};
+use crate::sshkey::{self, KeySource};
+
+/// A site and everything that should be set up with it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Blueprint {
+    site: SiteSpec,
+    #[serde(default)]
+    environments: Vec<EnvironmentSpec>,
+    #[serde(default)]
+    global_secrets: Vec<SecretSpec>,
+    #[serde(default)]
+    waf: WafSpec,
+    #[serde(default)]
+    ssh_keys: Vec<SshKeySpec>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct SiteSpec {
+    #[serde(rename(serialize = "your_customer_id"))]
+    customer_id: String,
+    dev_php_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct EnvironmentSpec {
+    name: String,
+    custom_domain: String,
+    php_version: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    is_production: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing)]
+    secrets: Vec<SecretSpec>,
+}
+
+/// A secret whose value is given inline or read from an environment
+/// variable, so blueprints can be committed without the values.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SecretSpec {
+    key: String,
+    value: Option<String>,
+    value_env: Option<String>,
+    #[serde(default = "default_true")]
+    secret: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WafSpec {
+    #[serde(default)]
+    rate_limits: Vec<RateLimitSpec>,
+    #[serde(default)]
+    blocked_ips: Vec<String>,
+    #[serde(default)]
+    blocked_referrers: Vec<String>,
+    #[serde(default)]
+    allowed_referrers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RateLimitSpec {
+    name: String,
+    request_count: u32,
+    timeframe: u32,
+    block_time: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    operator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variables: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transformations: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SshKeySpec {
+    name: Option<String>,
+    public_key: Option<String>,
+    public_key_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+struct SecretRequest<'a> {
+    key: &'a str,
+    value: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_secret: Option<bool>,
+}
+
+/// A blueprint with secrets and SSH keys resolved, ready to apply.
+struct Plan {
+    blueprint: Blueprint,
+    /// Secret values, in the order of environments' secrets followed by
+    /// global secrets.
+    secret_values: Vec<String>,
+    /// Key names and OpenSSH text.
+    ssh_keys: Vec<(String, String)>,
+}
+
+/// Something the blueprint created, and how to remove it on rollback.
+struct Created {
+    kind: &'static str,
+    name: String,
+    id: String,
+    rollback: Rollback,
+}
+
+enum Rollback {
+    /// Goes away with the site.
+    WithSite,
+    Delete(String),
+    /// The API returned no ID, so it has to be removed by hand.
+    Manual,
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn secret_value(secret: &SecretSpec) -> Result<String, ApiError> {
+    match (&secret.value, &secret.value_env) {
+        (Some(value), None) => Ok(value.clone()),
+        (None, Some(name)) => env::var(name).map_err(|_| {
+            ApiError::ValidationError(format!(
+                "Secret {} reads ${}, which is not set",
+                secret.key, name
+            ))
+        }),
+        _ => Err(ApiError::ValidationError(format!(
+            "Secret {} needs exactly one of value or value_env",
+            secret.key
+        ))),
+    }
+}
+
+/// Parses the blueprint and checks everything that can be checked before
+/// any request is made.
+fn prepare(text: &str) -> Result<Plan, ApiError> {
+    let blueprint: Blueprint = serde_yaml::from_str(text)
+        .map_err(|e| ApiError::ValidationError(format!("Invalid blueprint: {}", e)))?;
+
+    let mut secret_values = Vec::new();
+    for secret in blueprint
+        .environments
+        .iter()
+        .flat_map(|e| &e.secrets)
+        .chain(&blueprint.global_secrets)
+    {
+        secret_values.push(secret_value(secret)?);
+    }
+
+    let mut ssh_keys = Vec::new();
+    for spec in &blueprint.ssh_keys {
+        let source = match (&spec.public_key, &spec.public_key_file) {
+            (Some(text), None) => KeySource::Inline(text.clone()),
+            (None, Some(path)) => KeySource::File(expand_home(path)),
+            _ => {
+                return Err(ApiError::ValidationError(
+                    "Each SSH key needs exactly one of public_key or public_key_file".to_string(),
+                ));
+            }
+        };
+        let key = sshkey::load(source)?;
+        let name = spec.name.clone().or(key.comment.clone()).ok_or_else(|| {
+            ApiError::ValidationError("An SSH key has no comment; give it a name".to_string())
+        })?;
+        ssh_keys.push((name, key.to_openssh()));
+    }
+
+    Ok(Plan {
+        blueprint,
+        secret_values,
+        ssh_keys,
+    })
+}
+
+/// The ID of a created resource, which the API returns as a string or a
+/// number. A dry run creates nothing, so `placeholder` stands in for the ID
+/// in the paths and table that follow.
+fn id_of(response: &Value, placeholder: &str) -> Option<String> {
+    match &response["data"]["id"] {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ if is_dry_run() => Some(placeholder.to_string()),
+        _ => None,
+    }
+}
+
+fn missing_id(kind: &str, name: &str) -> ApiError {
+    ApiError::Other(format!(
+        "The API did not return an ID for the new {} {}",
+        kind, name
+    ))
+}
+
+fn apply(
+    client: &ApiClient,
+    plan: &Plan,
+    wait: Option<Wait>,
+    created: &mut Vec<Created>,
+) -> Result<(), ApiError> {
+    let blueprint = &plan.blueprint;
+    let response: Value = client.post("/api/v1/vector/sites", &blueprint.site)?;
+    let site_id = id_of(&response, "<site_id>");
+    created.push(Created {
+        kind: "site",
+        name: blueprint.site.customer_id.clone(),
+        id: site_id.clone().unwrap_or_else(|| "-".to_string()),
+        rollback: match &site_id {
+            Some(id) => Rollback::Delete(format!("/api/v1/vector/sites/{}", id)),
+            None => Rollback::Manual,
+        },
+    });
+    let site_id = site_id.ok_or_else(|| missing_id("site", &blueprint.site.customer_id))?;
+    if let Some(wait) = wait {
+        site::wait_for_status(client, &site_id, "active", wait)?;
+    }
+
+    let site_path = format!("/api/v1/vector/sites/{}", site_id);
+    let mut nested = |kind: &'static str, name: &str, response: Value| {
+        created.push(Created {
+            kind,
+            name: name.to_string(),
+            id: id_of(&response, "-").unwrap_or_else(|| "-".to_string()),
+            rollback: Rollback::WithSite,
+        });
+    };
+
+    for (name, public_key) in &plan.ssh_keys {
+        let response = client.post(
+            &format!("{}/ssh-keys", site_path),
+            &json!({"name": name, "public_key": public_key}),
+        )?;
+        nested("ssh key", name, response);
+    }
+
+    let waf = &blueprint.waf;
+    for rule in &waf.rate_limits {
+        let response = client.post(&format!("{}/waf/rate-limits", site_path), rule)?;
+        nested("rate limit", &rule.name, response);
+    }
+    for ip in &waf.blocked_ips {
+        let response = client.post(
+            &format!("{}/waf/blocked-ips", site_path),
+            &json!({ "ip": ip }),
+        )?;
+        nested("blocked ip", ip, response);
+    }
+    for (kind, hostnames, path) in [
+        (
+            "blocked referrer",
+            &waf.blocked_referrers,
+            "blocked-referrers",
+        ),
+        (
+            "allowed referrer",
+            &waf.allowed_referrers,
+            "allowed-referrers",
+        ),
+    ] {
+        for hostname in hostnames {
+            let response = client.post(
+                &format!("{}/waf/{}", site_path, path),
+                &json!({ "hostname": hostname }),
+            )?;
+            nested(kind, hostname, response);
+        }
+    }
+
+    let mut values = plan.secret_values.iter();
+    for environment in &blueprint.environments {
+        let response: Value = client.post(&format!("{}/environments", site_path), environment)?;
+        let env_id = id_of(&response, "<env_id>");
+        nested("environment", &environment.name, response);
+        let env_id = env_id.ok_or_else(|| missing_id("environment", &environment.name))?;
+
+        for secret in &environment.secrets {
+            let body = SecretRequest {
+                key: &secret.key,
+                value: values.next().map(String::as_str).unwrap_or_default(),
+                is_secret: (!secret.secret).then_some(false),
+            };
+            let response = client.post(
+                &format!("/api/v1/vector/environments/{}/secrets", env_id),
+                &body,
+            )?;
+            nested("secret", &secret.key, response);
+        }
+    }
+
+    // Global secrets live on the account, so they must be removed one by
+    // one on rollback.
+    for secret in &blueprint.global_secrets {
+        let body = SecretRequest {
+            key: &secret.key,
+            value: values.next().map(String::as_str).unwrap_or_default(),
+            is_secret: (!secret.secret).then_some(false),
+        };
+        let response = client.post("/api/v1/vector/global-secrets", &body)?;
+        let id = id_of(&response, "-");
+        created.push(Created {
+            kind: "global secret",
+            name: secret.key.clone(),
+            id: id.clone().unwrap_or_else(|| "-".to_string()),
+            rollback: match &id {
+                Some(id) => Rollback::Delete(format!("/api/v1/vector/global-secrets/{}", id)),
+                None => Rollback::Manual,
+            },
+        });
+        if id.is_none() {
+            return Err(missing_id("global secret", &secret.key));
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes what was created, newest first. Returns the errors of any
+/// deletions that failed or could not be attempted.
+fn rollback(client: &ApiClient, created: &[Created]) -> Vec<String> {
+    created
+        .iter()
+        .rev()
+        .filter_map(|c| match &c.rollback {
+            Rollback::WithSite => None,
+            Rollback::Delete(path) => client
+                .delete::<Value>(path)
+                .err()
+                .map(|e| format!("{} {} ({}): {}", c.kind, c.name, c.id, e)),
+            Rollback::Manual => Some(format!(
+                "{} {}: the API returned no ID; remove it manually",
+                c.kind, c.name
+            )),
+        })
+        .collect()
+}
+
+fn print_created(created: &[Created], format: OutputFormat, status: &str) {
+    if format == OutputFormat::Json {
+        let data: Vec<Value> = created
+            .iter()
+            .map(|c| json!({"kind": c.kind, "name": c.name, "id": c.id}))
+            .collect();
+        print_json(&json!({
+            "data": {
+                "site": created.first().map(|c| c.id.as_str()),
+                "status": status,
+                "created": data,
+            }
+        }));
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = created
+        .iter()
+        .map(|c| vec![c.kind.to_string(), c.name.clone(), c.id.clone()])
+        .collect();
+    print_table(vec!["Kind", "Name", "ID"], rows);
+}
+
+/// Creates a site and its environments, secrets, WAF rules and SSH keys
+/// from a YAML blueprint. If a step fails, everything created so far is
+/// rolled back unless `keep_partial` is set.
+pub fn create(
+    client: &ApiClient,
+    path: &Path,
+    wait: Option<Wait>,
+    keep_partial: bool,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| ApiError::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+    let plan = prepare(&text)?;
+
+    let mut created = Vec::new();
+    let Err(error) = apply(client, &plan, wait, &mut created) else {
+        let status = if is_dry_run() { "dry_run" } else { "created" };
+        print_created(&created, format, status);
+        if format != OutputFormat::Json {
+            print_message(&format!(
+                "Site {} created from {}.",
+                created[0].id,
+                path.display()
+            ));
+        }
+        return Ok(());
+    };
+
+    if created.is_empty() {
+        return Err(error);
+    }
+
+    eprintln!(
+        "Blueprint failed after creating {} resources: {}",
+        created.len(),
+        error
+    );
+    if keep_partial {
+        print_created(&created, format, "partial");
+        eprintln!("Created resources were kept.");
+        return Err(error);
+    }
+
+    let failures = rollback(client, &created);
+    print_created(&created, format, "rolled_back");
+    if failures.is_empty() {
+        eprintln!("Rolled back everything created above.");
+    } else {
+        eprintln!("Rollback was incomplete:");
+        for failure in &failures {
+            eprintln!("  {}", failure);
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLUEPRINT: &str = r#"
+site:
+  customer_id: acme
+  dev_php_version: "8.3"
+  tags: [client]
+environments:
+  - name: production
+    custom_domain: acme.example.com
+    php_version: "8.3"
+    is_production: true
+    secrets:
+      - key: API_TOKEN
+        value: abc
+      - key: PUBLIC_FLAG
+        value: "on"
+        secret: false
+global_secrets:
+  - key: SHARED
+    value: xyz
+waf:
+  blocked_ips: [203.0.113.7]
+  rate_limits:
+    - name: login
+      request_count: 10
+      timeframe: 60
+      block_time: 300
+ssh_keys:
+  - public_key: ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKFKjQ8L1jrVE9rOgV5+IsnkgSIqSnCP4uE7174+Cw9I jane@laptop
+"#;
+
+    #[test]
+    fn test_prepare_blueprint() {
+        let plan = prepare(BLUEPRINT).unwrap();
+        assert_eq!(plan.secret_values, ["abc", "on", "xyz"]);
+        assert_eq!(plan.ssh_keys[0].0, "jane@laptop");
+        assert_eq!(
+            serde_json::to_value(&plan.blueprint.site).unwrap(),
+            json!({"your_customer_id": "acme", "dev_php_version": "8.3", "tags": ["client"]})
+        );
+        assert_eq!(
+            serde_json::to_value(&plan.blueprint.environments[0]).unwrap(),
+            json!({
+                "name": "production",
+                "custom_domain": "acme.example.com",
+                "php_version": "8.3",
+                "is_production": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_prepare_rejects_mistakes() {
+        let typo = BLUEPRINT.replace("custom_domain", "custom_domian");
+        assert!(prepare(&typo).is_err());
+
+        let both = BLUEPRINT.replace("value: xyz", "value: xyz\n    value_env: SHARED");
+        assert!(prepare(&both).is_err());
+
+        let missing = BLUEPRINT.replace("value: xyz", "value_env: VECTOR_TEST_UNSET_SECRET");
+        assert!(prepare(&missing).is_err());
+
+        let bad_key = BLUEPRINT.replace("ssh-ed25519 AAAA", "ssh-ed25519 BBBB");
+        assert!(prepare(&bad_key).is_err());
+    }
+}
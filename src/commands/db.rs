@@ -3,7 +3,10 @@ use serde_json::Value;
 use std::path::Path;
 
 use crate::api::{ApiClient, ApiError};
-use crate::output::{OutputFormat, format_option, print_json, print_key_value, print_message};
+use crate::output::{
+    OutputFormat, format_bytes, format_duration_ms, format_option, format_status, format_timestamp,
+    print_json, print_key_value, print_message,
+};
 
 #[derive(Debug, Serialize)]
 struct CreateImportSessionRequest {
@@ -89,8 +92,8 @@ pub fn import_direct(
     let data = &response["data"];
     if data["success"].as_bool().unwrap_or(false) {
         print_message(&format!(
-            "Database imported successfully ({}ms).",
-            data["duration_ms"].as_u64().unwrap_or(0)
+            "Database imported successfully ({}).",
+            format_duration_ms(&Some(data["duration_ms"].as_u64().unwrap_or(0)))
         ));
     } else {
         return Err(ApiError::Other(
@@ -151,14 +154,17 @@ pub fn import_session_create(
     let data = &response["data"];
     print_key_value(vec![
         ("Import ID", data["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", data["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(data["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Upload URL",
             format_option(&data["upload_url"].as_str().map(String::from)),
         ),
         (
             "Expires",
-            format_option(&data["upload_expires_at"].as_str().map(String::from)),
+            format_timestamp(&data["upload_expires_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -192,7 +198,7 @@ pub fn import_session_run(
     print_message(&format!(
         "Import started: {} ({})",
         import_id,
-        data["status"].as_str().unwrap_or("-")
+        format_status(data["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
@@ -217,14 +223,17 @@ pub fn import_session_status(
     let data = &response["data"];
     print_key_value(vec![
         ("Import ID", data["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", data["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(data["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Filename",
             format_option(&data["filename"].as_str().map(String::from)),
         ),
         (
-            "Duration (ms)",
-            format_option(&data["duration_ms"].as_u64().map(|v| v.to_string())),
+            "Duration",
+            format_duration_ms(&data["duration_ms"].as_u64()),
         ),
         (
             "Error",
@@ -232,11 +241,11 @@ pub fn import_session_status(
         ),
         (
             "Created",
-            format_option(&data["created_at"].as_str().map(String::from)),
+            format_timestamp(&data["created_at"].as_str().map(String::from)),
         ),
         (
             "Completed",
-            format_option(&data["completed_at"].as_str().map(String::from)),
+            format_timestamp(&data["completed_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -267,7 +276,7 @@ pub fn export_create(
     print_message(&format!(
         "Export started: {} ({})",
         data["id"].as_str().unwrap_or("-"),
-        data["status"].as_str().unwrap_or("-")
+        format_status(data["status"].as_str().unwrap_or("-"))
     ));
     print_message("\nCheck status with:");
     print_message(&format!(
@@ -298,18 +307,18 @@ pub fn export_status(
     let data = &response["data"];
     print_key_value(vec![
         ("Export ID", data["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", data["status"].as_str().unwrap_or("-").to_string()),
         (
-            "Format",
-            format_option(&data["format"].as_str().map(String::from)),
+            "Status",
+            format_status(data["status"].as_str().unwrap_or("-")),
         ),
         (
-            "Size (bytes)",
-            format_option(&data["size_bytes"].as_u64().map(|v| v.to_string())),
+            "Format",
+            format_option(&data["format"].as_str().map(String::from)),
         ),
+        ("Size", format_bytes(&data["size_bytes"].as_u64())),
         (
-            "Duration (ms)",
-            format_option(&data["duration_ms"].as_u64().map(|v| v.to_string())),
+            "Duration",
+            format_duration_ms(&data["duration_ms"].as_u64()),
         ),
         (
             "Error",
@@ -321,15 +330,15 @@ pub fn export_status(
         ),
         (
             "Download Expires",
-            format_option(&data["download_expires_at"].as_str().map(String::from)),
+            format_timestamp(&data["download_expires_at"].as_str().map(String::from)),
         ),
         (
             "Created",
-            format_option(&data["created_at"].as_str().map(String::from)),
+            format_timestamp(&data["created_at"].as_str().map(String::from)),
         ),
         (
             "Completed",
-            format_option(&data["completed_at"].as_str().map(String::from)),
+            format_timestamp(&data["completed_at"].as_str().map(String::from)),
         ),
     ]);
 
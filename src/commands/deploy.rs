@@ -2,9 +2,10 @@ use serde::Serialize;
 use serde_json::Value;
 
 use crate::api::{ApiClient, ApiError};
+use crate::confirm::{self, Guard};
 use crate::output::{
-    OutputFormat, extract_pagination, format_option, print_json, print_key_value, print_message,
-    print_pagination, print_table,
+    OutputFormat, extract_pagination, format_option, format_status, format_timestamp, print_json,
+    print_key_value, print_message, print_pagination, print_table,
 };
 
 #[derive(Debug, Serialize)]
@@ -59,9 +60,9 @@ pub fn list(
         .map(|d| {
             vec![
                 d["id"].as_str().unwrap_or("-").to_string(),
-                d["status"].as_str().unwrap_or("-").to_string(),
+                format_status(d["status"].as_str().unwrap_or("-")),
                 format_option(&d["actor"].as_str().map(String::from)),
-                format_option(&d["created_at"].as_str().map(String::from)),
+                format_timestamp(&d["created_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -89,7 +90,7 @@ pub fn show(client: &ApiClient, deploy_id: &str, format: OutputFormat) -> Result
         ("ID", deploy["id"].as_str().unwrap_or("-").to_string()),
         (
             "Status",
-            deploy["status"].as_str().unwrap_or("-").to_string(),
+            format_status(deploy["status"].as_str().unwrap_or("-")),
         ),
         (
             "Actor",
@@ -97,24 +98,24 @@ pub fn show(client: &ApiClient, deploy_id: &str, format: OutputFormat) -> Result
         ),
         (
             "Created",
-            format_option(&deploy["created_at"].as_str().map(String::from)),
+            format_timestamp(&deploy["created_at"].as_str().map(String::from)),
         ),
         (
             "Updated",
-            format_option(&deploy["updated_at"].as_str().map(String::from)),
+            format_timestamp(&deploy["updated_at"].as_str().map(String::from)),
         ),
     ]);
 
     if let Some(stdout) = deploy["stdout"].as_str()
         && !stdout.is_empty()
     {
-        println!("\n--- stdout ---\n{}", stdout);
+        print_message(&format!("\n--- stdout ---\n{}", stdout));
     }
 
     if let Some(stderr) = deploy["stderr"].as_str()
         && !stderr.is_empty()
     {
-        println!("\n--- stderr ---\n{}", stderr);
+        print_message(&format!("\n--- stderr ---\n{}", stderr));
     }
 
     Ok(())
@@ -125,8 +126,21 @@ pub fn trigger(
     env_id: &str,
     include_uploads: bool,
     include_database: bool,
+    confirm_production: bool,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if include_database
+        && confirm::guard_production(
+            client,
+            env_id,
+            "deploy including the database",
+            confirm_production,
+        )? == Guard::Declined
+    {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let body = TriggerRequest {
         include_uploads,
         include_database,
@@ -145,7 +159,7 @@ pub fn trigger(
     print_message(&format!(
         "Deployment initiated: {} ({})",
         deploy["id"].as_str().unwrap_or("-"),
-        deploy["status"].as_str().unwrap_or("-")
+        format_status(deploy["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
@@ -155,8 +169,20 @@ pub fn rollback(
     client: &ApiClient,
     env_id: &str,
     target_deployment_id: Option<String>,
+    confirm_production: bool,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if confirm::guard_production(
+        client,
+        env_id,
+        "roll back to an earlier deployment",
+        confirm_production,
+    )? == Guard::Declined
+    {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let body = RollbackRequest {
         target_deployment_id,
     };
@@ -175,7 +201,7 @@ pub fn rollback(
     print_message(&format!(
         "Rollback initiated: {} ({})",
         deploy["id"].as_str().unwrap_or("-"),
-        deploy["status"].as_str().unwrap_or("-")
+        format_status(deploy["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
@@ -1,12 +1,19 @@
+use jiff::Timestamp;
 use serde::Serialize;
 use serde_json::Value;
 use std::path::Path;
 
 use crate::api::{ApiClient, ApiError};
+use crate::commands::site;
+use crate::commands::tags::{self, TagRequest};
+use crate::confirm::{self, Guard};
+use crate::diff::{self, Field};
 use crate::output::{
-    OutputFormat, extract_pagination, format_bool, format_option, print_json, print_key_value,
-    print_message, print_pagination, print_table,
+    OutputFormat, extract_pagination, format_bool, format_duration_ms, format_option,
+    format_status, format_timestamp, print_json, print_key_value, print_message, print_pagination,
+    print_table,
 };
+use crate::update::{self, Updated};
 
 #[derive(Debug, Serialize)]
 struct PaginationQuery {
@@ -94,7 +101,7 @@ pub fn list(
             vec![
                 e["id"].as_str().unwrap_or("-").to_string(),
                 e["name"].as_str().unwrap_or("-").to_string(),
-                e["status"].as_str().unwrap_or("-").to_string(),
+                format_status(e["status"].as_str().unwrap_or("-")),
                 format_bool(e["is_production"].as_bool().unwrap_or(false)),
                 format_option(&e["platform_domain"].as_str().map(String::from)),
             ]
@@ -126,7 +133,10 @@ pub fn show(client: &ApiClient, env_id: &str, format: OutputFormat) -> Result<()
     print_key_value(vec![
         ("ID", env["id"].as_str().unwrap_or("-").to_string()),
         ("Name", env["name"].as_str().unwrap_or("-").to_string()),
-        ("Status", env["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(env["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Production",
             format_bool(env["is_production"].as_bool().unwrap_or(false)),
@@ -162,11 +172,11 @@ pub fn show(client: &ApiClient, env_id: &str, format: OutputFormat) -> Result<()
         ("Tags", format_tags(&env["tags"])),
         (
             "Created",
-            format_option(&env["created_at"].as_str().map(String::from)),
+            format_timestamp(&env["created_at"].as_str().map(String::from)),
         ),
         (
             "Updated",
-            format_option(&env["updated_at"].as_str().map(String::from)),
+            format_timestamp(&env["updated_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -212,12 +222,20 @@ pub fn create(
     Ok(())
 }
 
+/// Fields `env update` can change.
+const UPDATE_FIELDS: &[Field] = &[
+    ("Name", "name"),
+    ("Custom Domain", "custom_domain"),
+    ("Tags", "tags"),
+];
+
 pub fn update(
     client: &ApiClient,
     env_id: &str,
     name: Option<String>,
     custom_domain: Option<String>,
     tags: Option<Vec<String>>,
+    unmodified_since: Option<Timestamp>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = UpdateEnvRequest {
@@ -226,7 +244,14 @@ pub fn update(
         tags,
     };
 
-    let response: Value = client.put(&format!("/api/v1/vector/environments/{}", env_id), &body)?;
+    let Updated { before, response } = update::put_unmodified(
+        client,
+        &format!("Environment {}", env_id),
+        &format!("/api/v1/vector/environments/{}", env_id),
+        &body,
+        unmodified_since,
+        UPDATE_FIELDS,
+    )?;
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -234,10 +259,74 @@ pub fn update(
     }
 
     print_message("Environment updated successfully.");
+    diff::print_changes(diff::changes(
+        &before["data"],
+        &response["data"],
+        &body,
+        UPDATE_FIELDS,
+        &[],
+    ));
     Ok(())
 }
 
-pub fn delete(client: &ApiClient, env_id: &str, format: OutputFormat) -> Result<(), ApiError> {
+pub fn tag(client: &ApiClient, request: TagRequest, format: OutputFormat) -> Result<(), ApiError> {
+    request.validate()?;
+    let ids: Vec<String> = match &request.matching_tag {
+        Some(tag) => tags::tagged(&site::fetch_all_environments(client)?, tag),
+        None => request.ids(),
+    };
+
+    tags::update(
+        client,
+        "/api/v1/vector/environments",
+        &ids,
+        &request,
+        format,
+    )
+}
+
+/// What `delete` shows before asking.
+const DELETE_DETAILS: &[(&str, &str)] = &[
+    ("ID", "id"),
+    ("Name", "name"),
+    ("Production", "is_production"),
+    ("Domain", "custom_domain"),
+    ("Platform Domain", "platform_domain"),
+];
+
+pub fn delete(
+    client: &ApiClient,
+    env_id: &str,
+    confirm_production: bool,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let action = format!("delete environment {}", env_id);
+    // Fail before any request when the deletion can't be confirmed.
+    confirm::ensure_confirmable(&action)?;
+
+    let (guard, env) = confirm::guard_production_env(
+        client,
+        env_id,
+        "delete the environment",
+        confirm_production,
+    )?;
+    let proceed = match guard {
+        Guard::Declined => false,
+        // Typing the name already confirmed the deletion.
+        Guard::Confirmed => true,
+        Guard::Proceed => confirm::confirm(&action, || {
+            let path = format!("/api/v1/vector/environments/{}", env_id);
+            match &env {
+                Some(env) => Ok(confirm::details(env, DELETE_DETAILS)),
+                None => confirm::fetch_details(client, &path, DELETE_DETAILS),
+            }
+        })?,
+    };
+    if !proceed {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!("/api/v1/vector/environments/{}", env_id))?;
 
     if format == OutputFormat::Json {
@@ -305,7 +394,7 @@ pub fn secret_list(
                 s["key"].as_str().unwrap_or("-").to_string(),
                 format_bool(s["is_secret"].as_bool().unwrap_or(true)),
                 format_option(&s["value"].as_str().map(String::from)),
-                format_option(&s["created_at"].as_str().map(String::from)),
+                format_timestamp(&s["created_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -346,11 +435,11 @@ pub fn secret_show(
         ),
         (
             "Created",
-            format_option(&secret["created_at"].as_str().map(String::from)),
+            format_timestamp(&secret["created_at"].as_str().map(String::from)),
         ),
         (
             "Updated",
-            format_option(&secret["updated_at"].as_str().map(String::from)),
+            format_timestamp(&secret["updated_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -391,12 +480,18 @@ pub fn secret_create(
     Ok(())
 }
 
+/// Fields `env secret update` can change; the value is only reported as
+/// changed or unchanged.
+const SECRET_UPDATE_FIELDS: &[Field] = &[("Key", "key"), ("Secret", "is_secret")];
+const SECRET_VALUE: &[Field] = &[("Value", "value")];
+
 pub fn secret_update(
     client: &ApiClient,
     secret_id: &str,
     key: Option<String>,
     value: Option<String>,
     no_secret: bool,
+    unmodified_since: Option<Timestamp>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = UpdateSecretRequest {
@@ -405,7 +500,14 @@ pub fn secret_update(
         is_secret: if no_secret { Some(false) } else { None },
     };
 
-    let response: Value = client.put(&format!("/api/v1/vector/secrets/{}", secret_id), &body)?;
+    let Updated { before, response } = update::put_unmodified(
+        client,
+        &format!("Secret {}", secret_id),
+        &format!("/api/v1/vector/secrets/{}", secret_id),
+        &body,
+        unmodified_since,
+        SECRET_UPDATE_FIELDS,
+    )?;
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -413,6 +515,13 @@ pub fn secret_update(
     }
 
     print_message("Secret updated successfully.");
+    diff::print_changes(diff::changes(
+        &before["data"],
+        &response["data"],
+        &body,
+        SECRET_UPDATE_FIELDS,
+        SECRET_VALUE,
+    ));
     Ok(())
 }
 
@@ -421,6 +530,17 @@ pub fn secret_delete(
     secret_id: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(&format!("delete secret {}", secret_id), || {
+        confirm::fetch_details(
+            client,
+            &format!("/api/v1/vector/secrets/{}", secret_id),
+            &[("ID", "id"), ("Key", "key")],
+        )
+    })? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!("/api/v1/vector/secrets/{}", secret_id))?;
 
     if format == OutputFormat::Json {
@@ -479,8 +599,21 @@ pub fn db_import(
     disable_foreign_keys: bool,
     search_replace_from: Option<String>,
     search_replace_to: Option<String>,
+    confirm_production: bool,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if drop_tables
+        && confirm::guard_production(
+            client,
+            env_id,
+            "drop all tables and import the file",
+            confirm_production,
+        )? == Guard::Declined
+    {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let metadata = std::fs::metadata(file_path)
         .map_err(|e| ApiError::Other(format!("Failed to read file: {}", e)))?;
 
@@ -519,8 +652,8 @@ pub fn db_import(
     let data = &response["data"];
     if data["success"].as_bool().unwrap_or(false) {
         print_message(&format!(
-            "Database imported successfully ({}ms).",
-            data["duration_ms"].as_u64().unwrap_or(0)
+            "Database imported successfully ({}).",
+            format_duration_ms(&Some(data["duration_ms"].as_u64().unwrap_or(0)))
         ));
     } else {
         return Err(ApiError::Other(
@@ -581,14 +714,17 @@ pub fn db_import_session_create(
     let data = &response["data"];
     print_key_value(vec![
         ("Import ID", data["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", data["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(data["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Upload URL",
             format_option(&data["upload_url"].as_str().map(String::from)),
         ),
         (
             "Expires",
-            format_option(&data["upload_expires_at"].as_str().map(String::from)),
+            format_timestamp(&data["upload_expires_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -622,7 +758,7 @@ pub fn db_import_session_run(
     print_message(&format!(
         "Import started: {} ({})",
         import_id,
-        data["status"].as_str().unwrap_or("-")
+        format_status(data["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
@@ -647,14 +783,17 @@ pub fn db_import_session_status(
     let data = &response["data"];
     print_key_value(vec![
         ("Import ID", data["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", data["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(data["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Filename",
             format_option(&data["filename"].as_str().map(String::from)),
         ),
         (
-            "Duration (ms)",
-            format_option(&data["duration_ms"].as_u64().map(|v| v.to_string())),
+            "Duration",
+            format_duration_ms(&data["duration_ms"].as_u64()),
         ),
         (
             "Error",
@@ -662,11 +801,11 @@ pub fn db_import_session_status(
         ),
         (
             "Created",
-            format_option(&data["created_at"].as_str().map(String::from)),
+            format_timestamp(&data["created_at"].as_str().map(String::from)),
         ),
         (
             "Completed",
-            format_option(&data["completed_at"].as_str().map(String::from)),
+            format_timestamp(&data["completed_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -678,8 +817,20 @@ pub fn db_promote(
     env_id: &str,
     drop_tables: bool,
     disable_foreign_keys: bool,
+    confirm_production: bool,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if confirm::guard_production(
+        client,
+        env_id,
+        "replace its database with the dev database",
+        confirm_production,
+    )? == Guard::Declined
+    {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let body = PromoteRequest {
         drop_tables,
         disable_foreign_keys,
@@ -699,7 +850,7 @@ pub fn db_promote(
     print_message(&format!(
         "Promote started: {} ({})",
         data["id"].as_str().unwrap_or("-"),
-        data["status"].as_str().unwrap_or("-")
+        format_status(data["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
@@ -724,10 +875,13 @@ pub fn db_promote_status(
     let data = &response["data"];
     print_key_value(vec![
         ("Promote ID", data["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", data["status"].as_str().unwrap_or("-").to_string()),
         (
-            "Duration (ms)",
-            format_option(&data["duration_ms"].as_u64().map(|v| v.to_string())),
+            "Status",
+            format_status(data["status"].as_str().unwrap_or("-")),
+        ),
+        (
+            "Duration",
+            format_duration_ms(&data["duration_ms"].as_u64()),
         ),
         (
             "Error",
@@ -735,11 +889,11 @@ pub fn db_promote_status(
         ),
         (
             "Created",
-            format_option(&data["created_at"].as_str().map(String::from)),
+            format_timestamp(&data["created_at"].as_str().map(String::from)),
         ),
         (
             "Completed",
-            format_option(&data["completed_at"].as_str().map(String::from)),
+            format_timestamp(&data["completed_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -3,7 +3,8 @@ use serde_json::Value;
 
 use crate::api::{ApiClient, ApiError};
 use crate::output::{
-    OutputFormat, extract_pagination, format_option, print_json, print_pagination, print_table,
+    OutputFormat, extract_pagination, format_timestamp, print_json, print_message,
+    print_pagination, print_table,
 };
 
 #[derive(Debug, Serialize)]
@@ -49,7 +50,7 @@ pub fn list(
         .ok_or_else(|| ApiError::Other("Invalid response format".to_string()))?;
 
     if events.is_empty() {
-        println!("No events found.");
+        print_message("No events found.");
         return Ok(());
     }
 
@@ -61,7 +62,7 @@ pub fn list(
                 e["event"].as_str().unwrap_or("-").to_string(),
                 format_actor(&e["actor"]),
                 format_resource(&e["resource"]),
-                format_option(&e["created_at"].as_str().map(String::from)),
+                format_timestamp(&e["created_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -0,0 +1,1089 @@
+use clap::ValueEnum;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use jiff::Timestamp;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::api::{ApiClient, ApiError};
+use crate::output::{
+    OutputFormat, format_level, is_log_level, print_json, print_message, print_table, write_atomic,
+};
+
+// Well-known field names, in order of preference, for the leading columns.
+const TIME_FIELDS: [&str; 4] = ["_time", "timestamp", "time", "@timestamp"];
+const LEVEL_FIELDS: [&str; 3] = ["level", "severity", "log_level"];
+const MESSAGE_FIELDS: [&str; 3] = ["message", "msg", "body"];
+const ID_FIELDS: [&str; 3] = ["_rowId", "id", "_id"];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LogsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// How log rows are rendered in table output.
+#[derive(Debug, Default)]
+pub struct LogDisplay {
+    /// Columns to show, by field name. `None` picks a default set.
+    pub fields: Option<Vec<String>>,
+    /// Print each row exactly as returned by the API, one per line.
+    pub raw: bool,
+}
+
+/// File formats for `site logs export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogExportFormat {
+    /// One JSON object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+#[derive(Debug)]
+pub struct ExportOptions {
+    pub path: PathBuf,
+    /// `None` picks a format from the file extension.
+    pub format: Option<LogExportFormat>,
+    pub gzip: bool,
+    pub resume: bool,
+}
+
+#[derive(Debug)]
+pub struct SearchOptions {
+    pub pattern: Option<String>,
+    pub ignore_case: bool,
+    /// Aggregate matches by this field instead of listing them.
+    pub count_by: Option<String>,
+    pub top: usize,
+    pub fields: Option<Vec<String>>,
+    pub max_pages: Option<u32>,
+}
+
+/// Progress saved next to an export so it can be resumed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ExportState {
+    /// The original filters plus the cursor for the next page.
+    query: LogsQuery,
+    rows: u64,
+    /// Length of the output file when the cursor was saved. A resumed export
+    /// cuts the file back to this, dropping anything written after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    /// CSV columns, fixed by the first page so resumed files stay consistent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    columns: Option<Vec<String>>,
+}
+
+/// One table from the Axiom-style log format: data.logs.tables[]
+#[derive(Debug)]
+struct LogTable {
+    fields: Vec<String>,
+    rows: Vec<LogRow>,
+}
+
+#[derive(Debug)]
+struct LogRow {
+    raw: Value,
+    cells: Vec<Value>,
+}
+
+impl LogTable {
+    fn from_value(table: &Value) -> Self {
+        let mut fields: Vec<String> = table["fields"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|f| f["name"].as_str().or_else(|| f.as_str()))
+            .map(String::from)
+            .collect();
+
+        let rows: Vec<LogRow> = if let Some(rows) = table["rows"].as_array() {
+            if fields.is_empty()
+                && let Some(first) = rows.iter().find_map(Value::as_object)
+            {
+                fields = first.keys().cloned().collect();
+            }
+            rows.iter()
+                .map(|row| LogRow {
+                    raw: row.clone(),
+                    cells: match row {
+                        Value::Array(cells) => cells.clone(),
+                        Value::Object(map) => fields
+                            .iter()
+                            .map(|f| map.get(f).cloned().unwrap_or(Value::Null))
+                            .collect(),
+                        other => vec![other.clone()],
+                    },
+                })
+                .collect()
+        } else if let Some(columns) = table["columns"].as_array() {
+            // Column-major tables hold one array of values per field.
+            let len = columns
+                .iter()
+                .filter_map(Value::as_array)
+                .map(Vec::len)
+                .max()
+                .unwrap_or(0);
+            (0..len)
+                .map(|i| {
+                    let cells: Vec<Value> = columns
+                        .iter()
+                        .map(|c| c.get(i).cloned().unwrap_or(Value::Null))
+                        .collect();
+                    LogRow {
+                        raw: Value::Array(cells.clone()),
+                        cells,
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Rows without a schema (or wider than it) get positional names.
+        let width = rows.iter().map(|r| r.cells.len()).max().unwrap_or(0);
+        while fields.len() < width {
+            fields.push(format!("field{}", fields.len() + 1));
+        }
+
+        LogTable { fields, rows }
+    }
+
+    fn position(&self, candidates: &[&str]) -> Option<usize> {
+        candidates
+            .iter()
+            .find_map(|name| self.fields.iter().position(|f| f == name))
+    }
+
+    /// Columns to render, as indexes into `fields`. Requested names the
+    /// table doesn't have are kept as empty columns so tables line up.
+    fn columns(&self, requested: Option<&[String]>) -> Vec<(String, Option<usize>)> {
+        if let Some(requested) = requested {
+            return requested
+                .iter()
+                .map(|name| (name.clone(), self.fields.iter().position(|f| f == name)))
+                .collect();
+        }
+
+        let leading: Vec<usize> = [
+            self.position(&TIME_FIELDS),
+            self.position(&LEVEL_FIELDS),
+            self.position(&MESSAGE_FIELDS),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        // Other underscore-prefixed fields are Axiom internals (_sysTime, _rowId).
+        let rest = (0..self.fields.len())
+            .filter(|i| !leading.contains(i) && !self.fields[*i].starts_with('_'));
+
+        leading
+            .iter()
+            .copied()
+            .chain(rest)
+            .map(|i| (self.fields[i].clone(), Some(i)))
+            .collect()
+    }
+}
+
+fn log_tables(response: &Value) -> Vec<LogTable> {
+    response["data"]["logs"]["tables"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(LogTable::from_value)
+        .collect()
+}
+
+fn format_cell(value: &Value, is_level: bool) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) if is_level || is_log_level(s) => format_level(s),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A row as a JSON object keyed by field name.
+fn row_object(fields: &[String], row: &LogRow) -> Map<String, Value> {
+    if let Value::Object(map) = &row.raw {
+        return map.clone();
+    }
+    fields
+        .iter()
+        .cloned()
+        .zip(row.cells.iter().cloned())
+        .collect()
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => csv_field(s),
+        other => csv_field(&other.to_string()),
+    }
+}
+
+/// Renders `rows` as the cells of the columns chosen by `display`.
+fn render_rows(
+    table: &LogTable,
+    rows: &[&LogRow],
+    display: &LogDisplay,
+) -> (Vec<String>, Vec<Vec<String>>) {
+    let columns = table.columns(display.fields.as_deref());
+    let level = table.position(&LEVEL_FIELDS);
+    let rendered = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|(_, index)| match index.and_then(|i| row.cells.get(i)) {
+                    Some(value) => format_cell(value, *index == level),
+                    None => "-".to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    let headers = columns.into_iter().map(|(name, _)| name).collect();
+    (headers, rendered)
+}
+
+fn print_log_rows(table: &LogTable, rows: &[&LogRow], display: &LogDisplay) {
+    if rows.is_empty() {
+        return;
+    }
+
+    if display.raw {
+        for row in rows {
+            print_message(&row.raw.to_string());
+        }
+        return;
+    }
+
+    let (headers, rendered) = render_rows(table, rows, display);
+    print_table(headers.iter().map(String::as_str).collect(), rendered);
+}
+
+/// Prints rows as they arrive in follow mode: one line per row, without a
+/// table frame, or one JSON object per row.
+fn print_followed_rows(
+    table: &LogTable,
+    rows: &[&LogRow],
+    display: &LogDisplay,
+    format: OutputFormat,
+) {
+    if format == OutputFormat::Json {
+        for row in rows {
+            print_message(&Value::Object(row_object(&table.fields, row)).to_string());
+        }
+        return;
+    }
+    if display.raw {
+        print_log_rows(table, rows, display);
+        return;
+    }
+    for cells in render_rows(table, rows, display).1 {
+        print_message(&cells.join("  "));
+    }
+}
+
+/// Remembers which rows follow mode has printed. A row is identified by
+/// its id field, or by its content when the table has none. Rows older
+/// than the newest printed timestamp are never printed again, so only the
+/// rows at that timestamp need to be remembered.
+#[derive(Debug, Default)]
+struct FollowCursor {
+    newest: Option<Timestamp>,
+    seen: HashSet<String>,
+}
+
+impl FollowCursor {
+    fn entry(table: &LogTable, row: &LogRow) -> (Option<Timestamp>, String) {
+        let cell = |candidates: &[&str]| {
+            table
+                .position(candidates)
+                .and_then(|i| row.cells.get(i))
+                .filter(|v| !v.is_null())
+        };
+        let time = cell(&TIME_FIELDS)
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse().ok());
+        let key = cell(&ID_FIELDS).unwrap_or(&row.raw).to_string();
+        (time, key)
+    }
+
+    fn is_new(&self, table: &LogTable, row: &LogRow) -> bool {
+        let (time, key) = Self::entry(table, row);
+        match (time, self.newest) {
+            (Some(time), Some(newest)) if time < newest => false,
+            (Some(time), Some(newest)) if time > newest => true,
+            _ => !self.seen.contains(&key),
+        }
+    }
+
+    fn record(&mut self, table: &LogTable, row: &LogRow) {
+        let (time, key) = Self::entry(table, row);
+        match (time, self.newest) {
+            (Some(time), Some(newest)) if time < newest => return,
+            (Some(time), newest) if newest != Some(time) => {
+                self.newest = Some(time);
+                self.seen.clear();
+            }
+            _ => {}
+        }
+        self.seen.insert(key);
+    }
+}
+
+pub fn show(
+    client: &ApiClient,
+    id: &str,
+    query: LogsQuery,
+    follow: bool,
+    interval: u64,
+    display: LogDisplay,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    if follow {
+        return follow_logs(
+            client,
+            id,
+            query,
+            Duration::from_secs(interval),
+            &display,
+            format,
+        );
+    }
+
+    let response: Value =
+        client.get_with_query(&format!("/api/v1/vector/sites/{}/logs", id), &query)?;
+
+    if format == OutputFormat::Json {
+        print_json(&response);
+        return Ok(());
+    }
+
+    if response["data"]["logs"]["tables"].is_array() {
+        for table in log_tables(&response) {
+            let rows: Vec<&LogRow> = table.rows.iter().collect();
+            print_log_rows(&table, &rows, &display);
+        }
+
+        // Show pagination info if there are more results
+        if response["data"]["has_more"].as_bool().unwrap_or(false)
+            && let Some(next_cursor) = response["data"]["cursor"].as_str()
+        {
+            eprintln!();
+            eprintln!(
+                "More results available. Use --cursor {} to continue.",
+                next_cursor
+            );
+        }
+    } else {
+        print_message("No logs available.");
+    }
+
+    Ok(())
+}
+
+fn follow_logs(
+    client: &ApiClient,
+    id: &str,
+    mut query: LogsQuery,
+    interval: Duration,
+    display: &LogDisplay,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let stopped = interrupt_flag()?;
+
+    let path = format!("/api/v1/vector/sites/{}/logs", id);
+    let mut cursor = FollowCursor::default();
+
+    while !stopped.load(Ordering::SeqCst) {
+        let response: Value = client.get_with_query(&path, &query)?;
+        let tables = log_tables(&response);
+
+        // Polling the same cursor can return rows that were already printed.
+        // Rows are checked before any are recorded, since a page may list
+        // the newest rows first.
+        let fresh: Vec<Vec<&LogRow>> = tables
+            .iter()
+            .map(|table| {
+                table
+                    .rows
+                    .iter()
+                    .filter(|row| cursor.is_new(table, row))
+                    .collect()
+            })
+            .collect();
+        for (table, rows) in tables.iter().zip(&fresh) {
+            print_followed_rows(table, rows, display, format);
+            for row in rows {
+                cursor.record(table, row);
+            }
+        }
+
+        if let Some(next_cursor) = response["data"]["cursor"].as_str() {
+            query.cursor = Some(next_cursor.to_string());
+        }
+        if response["data"]["has_more"].as_bool().unwrap_or(false) {
+            continue;
+        }
+
+        sleep_unless_stopped(interval, &stopped);
+    }
+
+    Ok(())
+}
+
+/// Returns a flag that is set when the user presses Ctrl-C, so long-running
+/// commands can stop at a safe point instead of being killed.
+fn interrupt_flag() -> Result<Arc<AtomicBool>, ApiError> {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stopped);
+    ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst))
+        .map_err(|e| ApiError::Other(format!("Failed to install Ctrl-C handler: {}", e)))?;
+    Ok(stopped)
+}
+
+fn sleep_unless_stopped(duration: Duration, stopped: &AtomicBool) {
+    let step = Duration::from_millis(100);
+    let mut waited = Duration::ZERO;
+    while waited < duration && !stopped.load(Ordering::SeqCst) {
+        thread::sleep(step);
+        waited += step;
+    }
+}
+
+pub fn export(
+    client: &ApiClient,
+    id: &str,
+    query: LogsQuery,
+    options: ExportOptions,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let path = &options.path;
+    let name = path.to_string_lossy();
+    let gzip = options.gzip || name.ends_with(".gz");
+    let file_format = options.format.unwrap_or_else(|| {
+        if name.trim_end_matches(".gz").ends_with(".csv") {
+            LogExportFormat::Csv
+        } else {
+            LogExportFormat::Ndjson
+        }
+    });
+    let state_path = export_state_path(path);
+    let mut state = initial_export_state(&state_path, query, options.resume)?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(!options.resume)
+        .open(path)
+        .map_err(|e| file_error(path, e))?;
+    if let Some(bytes) = state.bytes {
+        file.set_len(bytes).map_err(|e| file_error(path, e))?;
+    }
+    file.seek(SeekFrom::End(0))
+        .map_err(|e| file_error(path, e))?;
+    let mut writer = ExportWriter { file, gzip };
+
+    let stopped = interrupt_flag()?;
+    let progress = atty::is(atty::Stream::Stderr);
+
+    let result = export_pages(
+        client,
+        &format!("/api/v1/vector/sites/{}/logs", id),
+        &mut writer,
+        file_format,
+        &mut state,
+        path,
+        &stopped,
+        progress,
+    );
+    if progress {
+        eprintln!();
+    }
+
+    if let Err(e) = result {
+        if state_path.exists() {
+            eprintln!("Partial export saved. Run again with --resume to continue.");
+        }
+        return Err(e);
+    }
+    let _ = fs::remove_file(&state_path);
+
+    if format == OutputFormat::Json {
+        print_json(&json!({
+            "path": path,
+            "format": file_format.to_possible_value().map(|v| v.get_name().to_string()),
+            "gzip": gzip,
+            "rows": state.rows,
+        }));
+        return Ok(());
+    }
+
+    print_message(&format!(
+        "Exported {} log entries to {}.",
+        state.rows,
+        path.display()
+    ));
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn export_pages(
+    client: &ApiClient,
+    api_path: &str,
+    writer: &mut ExportWriter,
+    file_format: LogExportFormat,
+    state: &mut ExportState,
+    path: &Path,
+    stopped: &AtomicBool,
+    progress: bool,
+) -> Result<(), ApiError> {
+    let state_path = export_state_path(path);
+    loop {
+        let response: Value = client.get_with_query(api_path, &state.query)?;
+
+        let mut out = String::new();
+        for table in log_tables(&response) {
+            if file_format == LogExportFormat::Csv
+                && state.columns.is_none()
+                && !table.rows.is_empty()
+            {
+                let header: Vec<String> = table.fields.iter().map(|f| csv_field(f)).collect();
+                out.push_str(&header.join(","));
+                out.push('\n');
+                state.columns = Some(table.fields.clone());
+            }
+
+            for row in &table.rows {
+                let object = row_object(&table.fields, row);
+                let line = match (file_format, &state.columns) {
+                    (LogExportFormat::Csv, Some(columns)) => {
+                        // Fields missing from the first page's schema are dropped.
+                        let values: Vec<String> = columns
+                            .iter()
+                            .map(|c| object.get(c).map(csv_value).unwrap_or_default())
+                            .collect();
+                        values.join(",")
+                    }
+                    _ => Value::Object(object).to_string(),
+                };
+                out.push_str(&line);
+                out.push('\n');
+                state.rows += 1;
+            }
+        }
+        state.bytes = Some(
+            writer
+                .write_page(out.as_bytes())
+                .map_err(|e| file_error(path, e))?,
+        );
+
+        if progress {
+            eprint!("\rExported {} log entries...", state.rows);
+        }
+
+        let next_cursor = response["data"]["cursor"].as_str();
+        if !response["data"]["has_more"].as_bool().unwrap_or(false) || next_cursor.is_none() {
+            return Ok(());
+        }
+        state.query.cursor = next_cursor.map(String::from);
+        save_export_state(&state_path, state)?;
+
+        if stopped.load(Ordering::SeqCst) {
+            return Err(ApiError::Other(format!(
+                "Export interrupted after {} log entries",
+                state.rows
+            )));
+        }
+    }
+}
+
+pub fn search(
+    client: &ApiClient,
+    id: &str,
+    mut query: LogsQuery,
+    options: SearchOptions,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let pattern = options
+        .pattern
+        .as_deref()
+        .map(|p| {
+            RegexBuilder::new(p)
+                .case_insensitive(options.ignore_case)
+                .build()
+                .map_err(|e| ApiError::ValidationError(format!("Invalid pattern: {}", e)))
+        })
+        .transpose()?;
+
+    let stopped = interrupt_flag()?;
+    let progress = atty::is(atty::Stream::Stderr);
+    let path = format!("/api/v1/vector/sites/{}/logs", id);
+
+    let mut scanned: u64 = 0;
+    let mut matched: u64 = 0;
+    let mut pages: u32 = 0;
+    let mut tables: Vec<LogTable> = Vec::new();
+    let mut counts: HashMap<String, (Value, u64)> = HashMap::new();
+
+    loop {
+        let response: Value = client.get_with_query(&path, &query)?;
+        pages += 1;
+
+        for table in log_tables(&response) {
+            scanned += table.rows.len() as u64;
+            let hits: Vec<LogRow> = table
+                .rows
+                .into_iter()
+                .filter(|row| pattern.as_ref().is_none_or(|re| row_matches(re, row)))
+                .collect();
+            matched += hits.len() as u64;
+
+            if let Some(field) = &options.count_by {
+                for row in &hits {
+                    let value = row_object(&table.fields, row)
+                        .remove(field)
+                        .unwrap_or(Value::Null);
+                    counts.entry(value.to_string()).or_insert((value, 0)).1 += 1;
+                }
+            } else if !hits.is_empty() {
+                // Pages usually share a schema; keep them in one table.
+                match tables.last_mut() {
+                    Some(last) if last.fields == table.fields => last.rows.extend(hits),
+                    _ => tables.push(LogTable {
+                        fields: table.fields,
+                        rows: hits,
+                    }),
+                }
+            }
+        }
+
+        if progress {
+            eprint!("\rSearched {} log entries, {} matched...", scanned, matched);
+        }
+
+        let next_cursor = response["data"]["cursor"].as_str();
+        let has_more = response["data"]["has_more"].as_bool().unwrap_or(false);
+        if !has_more
+            || next_cursor.is_none()
+            || options.max_pages.is_some_and(|max| pages >= max)
+            || stopped.load(Ordering::SeqCst)
+        {
+            break;
+        }
+        query.cursor = next_cursor.map(String::from);
+    }
+
+    if progress {
+        eprintln!();
+    }
+    if stopped.load(Ordering::SeqCst) {
+        eprintln!("Interrupted. Showing results for the entries searched so far.");
+    }
+
+    let mut counts: Vec<(Value, u64)> = counts.into_values().collect();
+    counts.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+    });
+    counts.truncate(options.top);
+
+    if format == OutputFormat::Json {
+        let results = match &options.count_by {
+            Some(field) => json!({
+                "field": field,
+                "counts": counts
+                    .iter()
+                    .map(|(value, count)| json!({"value": value, "count": count}))
+                    .collect::<Vec<_>>(),
+            }),
+            None => json!({
+                "rows": tables
+                    .iter()
+                    .flat_map(|t| t.rows.iter().map(|r| Value::Object(row_object(&t.fields, r))))
+                    .collect::<Vec<_>>(),
+            }),
+        };
+        print_json(&json!({
+            "scanned": scanned,
+            "matched": matched,
+            "results": results,
+        }));
+        return Ok(());
+    }
+
+    if let Some(field) = &options.count_by {
+        if !counts.is_empty() {
+            let rows: Vec<Vec<String>> = counts
+                .iter()
+                .map(|(value, count)| vec![format_cell(value, false), count.to_string()])
+                .collect();
+            print_table(vec![field.as_str(), "Count"], rows);
+        }
+    } else {
+        let display = LogDisplay {
+            fields: options.fields,
+            raw: false,
+        };
+        for table in &tables {
+            let rows: Vec<&LogRow> = table.rows.iter().collect();
+            print_log_rows(table, &rows, &display);
+        }
+    }
+
+    print_message(&format!("{} of {} log entries matched.", matched, scanned));
+    Ok(())
+}
+
+fn row_matches(pattern: &Regex, row: &LogRow) -> bool {
+    row.cells.iter().any(|cell| match cell {
+        Value::Null => false,
+        Value::String(s) => pattern.is_match(s),
+        other => pattern.is_match(&other.to_string()),
+    })
+}
+
+/// The export file. Each page is written and synced in one go, as its own
+/// gzip member when compressing, so the file is valid up to the length
+/// saved with the cursor.
+struct ExportWriter {
+    file: File,
+    gzip: bool,
+}
+
+impl ExportWriter {
+    /// Appends `data` and returns the new length of the file.
+    fn write_page(&mut self, data: &[u8]) -> io::Result<u64> {
+        if !data.is_empty() {
+            if self.gzip {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                self.file.write_all(&encoder.finish()?)?;
+            } else {
+                self.file.write_all(data)?;
+            }
+            self.file.sync_data()?;
+        }
+        self.file.stream_position()
+    }
+}
+
+fn export_state_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".cursor");
+    PathBuf::from(name)
+}
+
+/// The progress an export starts from. A resumed export continues with the
+/// filters, cursor and row count it was started with; a `--limit` given
+/// again replaces the saved page size.
+fn initial_export_state(
+    state_path: &Path,
+    query: LogsQuery,
+    resume: bool,
+) -> Result<ExportState, ApiError> {
+    if !resume {
+        return Ok(ExportState {
+            query,
+            ..Default::default()
+        });
+    }
+    let mut state = load_export_state(state_path)?;
+    if query.limit.is_some() {
+        state.query.limit = query.limit;
+    }
+    Ok(state)
+}
+
+fn load_export_state(path: &Path) -> Result<ExportState, ApiError> {
+    let contents = fs::read_to_string(path).map_err(|_| {
+        ApiError::ValidationError(format!(
+            "No saved export progress found at {}",
+            path.display()
+        ))
+    })?;
+    serde_json::from_str(&contents).map_err(|e| {
+        ApiError::Other(format!(
+            "Invalid export progress file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn save_export_state(path: &Path, state: &ExportState) -> Result<(), ApiError> {
+    let contents = serde_json::to_string(state)
+        .map_err(|e| ApiError::Other(format!("Failed to save export progress: {}", e)))?;
+    write_atomic(path, contents.as_bytes(), false)
+}
+
+fn file_error(path: &Path, e: io::Error) -> ApiError {
+    ApiError::Other(format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn names(columns: &[(String, Option<usize>)]) -> Vec<&str> {
+        columns.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_log_table_uses_field_schema() {
+        let table = LogTable::from_value(&json!({
+            "fields": [
+                {"name": "_sysTime", "type": "datetime"},
+                {"name": "status", "type": "integer"},
+                {"name": "message", "type": "string"},
+                {"name": "_time", "type": "datetime"},
+                {"name": "level", "type": "string"}
+            ],
+            "rows": [["2025-01-15T10:00:00Z", 500, "boom", "2025-01-15T10:00:00Z", "error"]]
+        }));
+
+        assert_eq!(
+            names(&table.columns(None)),
+            ["_time", "level", "message", "status"]
+        );
+        assert_eq!(table.rows[0].cells[1], json!(500));
+    }
+
+    #[test]
+    fn test_log_table_requested_fields() {
+        let table = LogTable::from_value(&json!({
+            "fields": [{"name": "_time"}, {"name": "path"}],
+            "rows": [["2025-01-15T10:00:00Z", "/"]]
+        }));
+
+        let requested = vec!["path".to_string(), "missing".to_string()];
+        let columns = table.columns(Some(&requested));
+        assert_eq!(
+            columns,
+            [("path".to_string(), Some(1)), ("missing".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_log_table_column_major() {
+        let table = LogTable::from_value(&json!({
+            "fields": [{"name": "_time"}, {"name": "duration"}],
+            "columns": [["t1", "t2"], [1.5, null]]
+        }));
+
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[1].cells, [json!("t2"), Value::Null]);
+    }
+
+    #[test]
+    fn test_log_table_without_schema() {
+        let table = LogTable::from_value(&json!({
+            "rows": [["2025-01-15T10:00:00Z", "hello", "info"]]
+        }));
+
+        assert_eq!(table.fields, ["field1", "field2", "field3"]);
+    }
+
+    #[test]
+    fn test_follow_cursor_skips_printed_rows() {
+        let first = LogTable::from_value(&json!({
+            "fields": ["_time", "message"],
+            "rows": [
+                ["2026-10-18T10:00:02Z", "b"],
+                ["2026-10-18T10:00:01Z", "a"],
+            ]
+        }));
+        let mut cursor = FollowCursor::default();
+        assert!(first.rows.iter().all(|r| cursor.is_new(&first, r)));
+        for row in &first.rows {
+            cursor.record(&first, row);
+        }
+
+        // The next poll repeats "b", and "c" shares its timestamp.
+        let second = LogTable::from_value(&json!({
+            "fields": ["_time", "message"],
+            "rows": [
+                ["2026-10-18T10:00:01Z", "a"],
+                ["2026-10-18T10:00:02Z", "b"],
+                ["2026-10-18T10:00:02Z", "c"],
+                ["2026-10-18T10:00:03Z", "d"],
+            ]
+        }));
+        let fresh: Vec<&Value> = second
+            .rows
+            .iter()
+            .filter(|r| cursor.is_new(&second, r))
+            .map(|r| &r.cells[1])
+            .collect();
+        assert_eq!(fresh, vec!["c", "d"]);
+    }
+
+    #[test]
+    fn test_follow_cursor_uses_row_id() {
+        let table = LogTable::from_value(&json!({
+            "rows": [{"_rowId": "r1", "message": "retry"}]
+        }));
+        let again = LogTable::from_value(&json!({
+            "rows": [{"_rowId": "r1", "message": "retry", "_sysTime": "x"}]
+        }));
+        let mut cursor = FollowCursor::default();
+        cursor.record(&table, &table.rows[0]);
+        assert!(!cursor.is_new(&again, &again.rows[0]));
+    }
+
+    #[test]
+    fn test_row_object() {
+        let table = LogTable::from_value(&json!({
+            "fields": [{"name": "_time"}, {"name": "status"}],
+            "rows": [["t1", 200], {"_time": "t2", "status": null}]
+        }));
+
+        let first = row_object(&table.fields, &table.rows[0]);
+        assert_eq!(Value::Object(first), json!({"_time": "t1", "status": 200}));
+        let second = row_object(&table.fields, &table.rows[1]);
+        assert_eq!(
+            Value::Object(second),
+            json!({"_time": "t2", "status": null})
+        );
+    }
+
+    #[test]
+    fn test_row_matches() {
+        let table = LogTable::from_value(&json!({
+            "fields": [{"name": "path"}, {"name": "status"}, {"name": "user"}],
+            "rows": [["/wp-login.php", 403, null]]
+        }));
+        let row = &table.rows[0];
+
+        assert!(row_matches(&Regex::new("wp-login").unwrap(), row));
+        assert!(row_matches(&Regex::new("^40[0-9]$").unwrap(), row));
+        assert!(!row_matches(&Regex::new("null").unwrap(), row));
+    }
+
+    #[test]
+    fn test_csv_value() {
+        assert_eq!(csv_value(&json!("plain")), "plain");
+        assert_eq!(csv_value(&json!("a,b")), "\"a,b\"");
+        assert_eq!(csv_value(&json!("say \"hi\"")), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_value(&json!(404)), "404");
+        assert_eq!(csv_value(&Value::Null), "");
+    }
+
+    #[test]
+    fn test_export_state_path() {
+        assert_eq!(
+            export_state_path(Path::new("/tmp/logs.csv.gz")),
+            PathBuf::from("/tmp/logs.csv.gz.cursor")
+        );
+    }
+
+    #[test]
+    fn test_initial_export_state_resumes_from_cursor_file() {
+        let path =
+            std::env::temp_dir().join(format!("vector-resume-{}.cursor", std::process::id()));
+        let saved = ExportState {
+            query: LogsQuery {
+                level: Some("error".to_string()),
+                limit: Some(20),
+                cursor: Some("c2".to_string()),
+                ..Default::default()
+            },
+            rows: 40,
+            bytes: Some(1200),
+            columns: None,
+        };
+        save_export_state(&path, &saved).unwrap();
+
+        let state = initial_export_state(&path, LogsQuery::default(), true).unwrap();
+        assert_eq!(state.rows, 40);
+        assert_eq!(state.query.cursor.as_deref(), Some("c2"));
+        assert_eq!(state.query.level.as_deref(), Some("error"));
+        assert_eq!(state.query.limit, Some(20));
+        assert_eq!(state.bytes, Some(1200));
+
+        let query = LogsQuery {
+            limit: Some(50),
+            ..Default::default()
+        };
+        let state = initial_export_state(&path, query, true).unwrap();
+        assert_eq!(state.query.limit, Some(50));
+        assert_eq!(state.rows, 40);
+
+        fs::remove_file(&path).unwrap();
+        assert!(initial_export_state(&path, LogsQuery::default(), true).is_err());
+    }
+
+    #[test]
+    fn test_export_writer_resumes_at_saved_length() {
+        use flate2::read::MultiGzDecoder;
+        use std::io::Read;
+
+        let path = std::env::temp_dir().join(format!("vector-export-{}.gz", std::process::id()));
+        let mut writer = ExportWriter {
+            file: File::create(&path).unwrap(),
+            gzip: true,
+        };
+        let saved = writer.write_page(b"{\"n\":1}\n").unwrap();
+        // Killed after writing the next page but before saving its cursor.
+        writer.write_page(b"{\"n\":2}\n").unwrap();
+        drop(writer);
+
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(saved).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        let mut writer = ExportWriter { file, gzip: true };
+        writer.write_page(b"{\"n\":2}\n").unwrap();
+
+        let mut contents = String::new();
+        MultiGzDecoder::new(File::open(&path).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "{\"n\":1}\n{\"n\":2}\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_format_cell_keeps_numbers_and_nulls() {
+        assert_eq!(format_cell(&json!(42), false), "42");
+        assert_eq!(format_cell(&json!(0.25), false), "0.25");
+        assert_eq!(format_cell(&json!(true), false), "true");
+        assert_eq!(format_cell(&Value::Null, false), "-");
+        assert_eq!(format_cell(&json!({"a": 1}), false), r#"{"a":1}"#);
+    }
+}
@@ -1,11 +1,18 @@
 pub mod account;
 pub mod auth;
+pub mod blueprint;
+pub mod credentials;
 pub mod db;
 pub mod deploy;
 pub mod env;
 pub mod event;
+pub mod logs;
 pub mod mcp;
+pub mod purge;
 pub mod site;
+pub mod ssh;
+pub mod ssh_key;
 pub mod ssl;
+pub mod tags;
 pub mod waf;
 pub mod webhook;
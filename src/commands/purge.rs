@@ -0,0 +1,301 @@
+use flate2::read::GzDecoder;
+use regex::Regex;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read};
+use std::sync::LazyLock;
+use std::thread;
+use std::time::Duration;
+
+use crate::api::{ApiClient, ApiError};
+use crate::commands::site::SiteAction;
+use crate::output::{OutputFormat, format_status, print_json, print_message, print_table};
+
+/// Nested sitemap indexes are followed at most this deep.
+const MAX_SITEMAP_DEPTH: usize = 3;
+
+static LOC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<loc>\s*(.*?)\s*</loc>").expect("valid regex"));
+
+/// Where to read the URLs to purge from.
+#[derive(Debug)]
+pub enum UrlSource {
+    /// A file with one URL per line, or `-` for stdin.
+    List(String),
+    /// A sitemap or sitemap index, given as a URL or a local file.
+    Sitemap(String),
+}
+
+/// How purge requests are spread out over time.
+#[derive(Debug, Clone, Copy)]
+pub struct Throttle {
+    /// Requests sent at once.
+    pub batch_size: usize,
+    /// Pause between batches.
+    pub delay: Duration,
+}
+
+/// Purges every URL from `source` on the site, one request per URL, sent
+/// in throttled batches. Failed URLs are reported at the end.
+pub fn purge_urls(
+    client: &ApiClient,
+    id: &str,
+    source: UrlSource,
+    throttle: Throttle,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let urls = match source {
+        UrlSource::List(path) => read_url_list(&path)?,
+        UrlSource::Sitemap(location) => {
+            let mut visited = HashSet::new();
+            sitemap_urls(&location, 0, &mut visited)?
+        }
+    };
+    if urls.is_empty() {
+        return Err(ApiError::ValidationError("No URLs to purge".to_string()));
+    }
+
+    let progress = atty::is(atty::Stream::Stderr);
+    let mut failures: Vec<(String, String)> = Vec::new();
+    let batch_size = throttle.batch_size.max(1);
+
+    for (n, batch) in urls.chunks(batch_size).enumerate() {
+        if n > 0 && !throttle.delay.is_zero() {
+            thread::sleep(throttle.delay);
+        }
+
+        let outcomes: Vec<Result<Value, ApiError>> = thread::scope(|s| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|url| {
+                    s.spawn(move || {
+                        SiteAction::PurgeCache {
+                            cache_tag: None,
+                            url: Some(url.clone()),
+                        }
+                        .run(client, id, None)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| {
+                    h.join().unwrap_or_else(|_| {
+                        Err(ApiError::Other("Purge request panicked".to_string()))
+                    })
+                })
+                .collect()
+        });
+
+        for (url, outcome) in batch.iter().zip(outcomes) {
+            if let Err(e) = outcome {
+                failures.push((url.clone(), e.to_string()));
+            }
+        }
+        if progress {
+            eprint!(
+                "\rPurged {} of {} URLs...",
+                (n * batch_size + batch.len()).min(urls.len()),
+                urls.len()
+            );
+        }
+    }
+    if progress {
+        eprintln!();
+    }
+
+    let purged = urls.len() - failures.len();
+    if format == OutputFormat::Json {
+        let failed: Vec<Value> = failures
+            .iter()
+            .map(|(url, error)| json!({"url": url, "error": error}))
+            .collect();
+        print_json(&json!({
+            "data": {"total": urls.len(), "purged": purged, "failed": failed}
+        }));
+    } else {
+        if !failures.is_empty() {
+            let rows: Vec<Vec<String>> = failures
+                .iter()
+                .map(|(url, error)| vec![url.clone(), format_status("failed"), error.clone()])
+                .collect();
+            print_table(vec!["URL", "Result", "Error"], rows);
+        }
+        print_message(&format!(
+            "Purged {} of {} URLs, {} failed.",
+            purged,
+            urls.len(),
+            failures.len()
+        ));
+    }
+
+    if !failures.is_empty() {
+        return Err(ApiError::Other(format!(
+            "{} of {} URLs failed to purge",
+            failures.len(),
+            urls.len()
+        )));
+    }
+    Ok(())
+}
+
+fn read_url_list(path: &str) -> Result<Vec<String>, ApiError> {
+    let mut input = String::new();
+    if path == "-" {
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| ApiError::Other(format!("Failed to read URLs from stdin: {}", e)))?;
+    } else {
+        input = fs::read_to_string(path)
+            .map_err(|e| ApiError::Other(format!("Failed to read {}: {}", path, e)))?;
+    }
+    Ok(parse_url_list(&input))
+}
+
+/// One URL per line. Blank lines and `#` comments are skipped, and
+/// duplicates are dropped.
+fn parse_url_list(input: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| seen.insert(line.to_string()))
+        .map(String::from)
+        .collect()
+}
+
+/// Collects page URLs from a sitemap, following sitemap indexes.
+fn sitemap_urls(
+    location: &str,
+    depth: usize,
+    visited: &mut HashSet<String>,
+) -> Result<Vec<String>, ApiError> {
+    if !visited.insert(location.to_string()) {
+        return Ok(Vec::new());
+    }
+
+    let xml = read_sitemap(location)?;
+    let (is_index, locs) = parse_sitemap(&xml);
+    if !is_index {
+        return Ok(locs);
+    }
+    if depth >= MAX_SITEMAP_DEPTH {
+        return Err(ApiError::ValidationError(format!(
+            "Sitemap indexes nested more than {} levels deep at {}",
+            MAX_SITEMAP_DEPTH, location
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut urls = Vec::new();
+    for child in locs {
+        for url in sitemap_urls(&child, depth + 1, visited)? {
+            if seen.insert(url.clone()) {
+                urls.push(url);
+            }
+        }
+    }
+    Ok(urls)
+}
+
+fn read_sitemap(location: &str) -> Result<String, ApiError> {
+    let bytes = if location.starts_with("http://") || location.starts_with("https://") {
+        let response = reqwest::blocking::Client::builder()
+            .user_agent(concat!("vector-cli/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .and_then(|client| client.get(location).send())
+            .and_then(|response| response.error_for_status())
+            .map_err(ApiError::NetworkError)?;
+        response.bytes().map_err(ApiError::NetworkError)?.to_vec()
+    } else {
+        fs::read(location)
+            .map_err(|e| ApiError::Other(format!("Failed to read {}: {}", location, e)))?
+    };
+
+    // Sitemaps are often served as .xml.gz.
+    let bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decoded)
+            .map_err(|e| ApiError::Other(format!("Failed to decompress {}: {}", location, e)))?;
+        decoded
+    } else {
+        bytes
+    };
+
+    String::from_utf8(bytes)
+        .map_err(|_| ApiError::Other(format!("Sitemap {} is not valid UTF-8", location)))
+}
+
+/// Returns whether the document is a sitemap index, and its `<loc>` values.
+fn parse_sitemap(xml: &str) -> (bool, Vec<String>) {
+    let is_index = xml.contains("<sitemapindex");
+    let locs = LOC
+        .captures_iter(xml)
+        .map(|c| unescape(c[1].trim_start_matches("<![CDATA[").trim_end_matches("]]>")))
+        .filter(|loc| !loc.is_empty())
+        .collect();
+    (is_index, locs)
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_list() {
+        let input =
+            "https://example.com/\n\n# comment\n  https://example.com/a  \nhttps://example.com/\n";
+        assert_eq!(
+            parse_url_list(input),
+            ["https://example.com/", "https://example.com/a"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/</loc><lastmod>2026-10-01</lastmod></url>
+  <url>
+    <loc>
+      https://example.com/?p=1&amp;lang=en
+    </loc>
+  </url>
+  <url><loc><![CDATA[https://example.com/about]]></loc></url>
+</urlset>"#;
+        let (is_index, locs) = parse_sitemap(xml);
+        assert!(!is_index);
+        assert_eq!(
+            locs,
+            [
+                "https://example.com/",
+                "https://example.com/?p=1&lang=en",
+                "https://example.com/about"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap_index() {
+        let xml = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/post-sitemap.xml</loc></sitemap>
+  <sitemap><loc>https://example.com/page-sitemap.xml</loc></sitemap>
+</sitemapindex>"#;
+        let (is_index, locs) = parse_sitemap(xml);
+        assert!(is_index);
+        assert_eq!(locs.len(), 2);
+    }
+}
@@ -1,11 +1,105 @@
+use jiff::Timestamp;
 use serde::Serialize;
-use serde_json::Value;
+use serde_json::{Value, json};
+use std::io::{self, Read};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::api::{ApiClient, ApiError};
+use crate::commands::tags::{self, TagRequest};
+use crate::confirm;
+use crate::diff::{self, Field};
 use crate::output::{
-    OutputFormat, extract_pagination, format_option, print_json, print_key_value, print_message,
-    print_pagination, print_table,
+    OutputFormat, extract_pagination, format_bool, format_option, format_status, format_timestamp,
+    print_json, print_key_value, print_message, print_pagination, print_table,
 };
+use crate::sshkey;
+use crate::update::{self, Updated};
+
+/// How long to poll for a site to reach a status, and how often.
+#[derive(Debug, Clone, Copy)]
+pub struct Wait {
+    pub timeout: Duration,
+    pub interval: Duration,
+}
+
+/// Operations that can be applied to one site or to many in bulk.
+#[derive(Debug)]
+pub enum SiteAction {
+    Suspend,
+    Unsuspend,
+    PurgeCache {
+        cache_tag: Option<String>,
+        url: Option<String>,
+    },
+    WpReconfig,
+}
+
+impl SiteAction {
+    /// What the action does to a site, for confirmation prompts.
+    fn verb(&self) -> &'static str {
+        match self {
+            SiteAction::Suspend => "suspend",
+            SiteAction::Unsuspend => "unsuspend",
+            SiteAction::PurgeCache { .. } => "purge the cache of",
+            SiteAction::WpReconfig => "run wp-reconfig on",
+        }
+    }
+
+    /// Sends the request, then waits for the site to settle if asked to.
+    pub fn run(&self, client: &ApiClient, id: &str, wait: Option<Wait>) -> Result<Value, ApiError> {
+        // Purges and reconfigs leave an active site active, so the wait needs
+        // to see the site change before its status counts as settled.
+        let before: Option<Value> = match wait {
+            Some(_) => {
+                Some(client.get::<Value>(&format!("/api/v1/vector/sites/{}", id))?["data"].take())
+            }
+            None => None,
+        };
+
+        let (response, settled): (Value, &str) = match self {
+            SiteAction::Suspend => (
+                client.put_empty(&format!("/api/v1/vector/sites/{}/suspend", id))?,
+                "suspended",
+            ),
+            SiteAction::Unsuspend => (
+                client.put_empty(&format!("/api/v1/vector/sites/{}/unsuspend", id))?,
+                "active",
+            ),
+            SiteAction::PurgeCache { cache_tag, url } => (
+                client.post(
+                    &format!("/api/v1/vector/sites/{}/purge-cache", id),
+                    &PurgeCacheRequest {
+                        cache_tag: cache_tag.clone(),
+                        url: url.clone(),
+                    },
+                )?,
+                "active",
+            ),
+            SiteAction::WpReconfig => (
+                client.post_empty(&format!("/api/v1/vector/sites/{}/wp/reconfig", id))?,
+                "active",
+            ),
+        };
+
+        match (wait, before) {
+            (Some(wait), Some(before)) => wait_for_change(client, id, settled, &before, wait),
+            _ => Ok(response),
+        }
+    }
+}
+
+/// Which sites a bulk operation applies to.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Read IDs from stdin, one per line. Otherwise every site matching
+    /// the filters below is selected.
+    pub stdin: bool,
+    pub tag: Option<String>,
+    pub customer_id: Option<String>,
+}
 
 #[derive(Debug, Serialize)]
 struct PaginationQuery {
@@ -13,6 +107,23 @@ struct PaginationQuery {
     per_page: u32,
 }
 
+#[derive(Debug, Serialize)]
+struct EnvironmentsFilter<'a> {
+    site: &'a str,
+}
+
+/// A page of a listing, with any filters the endpoint takes.
+#[derive(Debug, Serialize)]
+struct PageQuery<'a, F> {
+    #[serde(flatten)]
+    filter: &'a F,
+    page: u32,
+    per_page: u32,
+}
+
+/// How many detail requests `overview` keeps in flight.
+const CONCURRENCY: usize = 4;
+
 #[derive(Debug, Serialize)]
 struct CreateSiteRequest {
     your_customer_id: String,
@@ -47,24 +158,6 @@ struct PurgeCacheRequest {
     url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct LogsQuery {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    start_time: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end_time: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    limit: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    environment: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    deployment_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    level: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    cursor: Option<String>,
-}
-
 #[derive(Debug, Serialize)]
 struct CreateSshKeyRequest {
     name: String,
@@ -99,7 +192,7 @@ pub fn list(
         .map(|s| {
             vec![
                 s["id"].as_str().unwrap_or("-").to_string(),
-                s["status"].as_str().unwrap_or("-").to_string(),
+                format_status(s["status"].as_str().unwrap_or("-")),
                 format_option(&s["your_customer_id"].as_str().map(String::from)),
                 format_option(&s["dev_domain"].as_str().map(String::from)),
             ]
@@ -127,7 +220,10 @@ pub fn show(client: &ApiClient, id: &str, format: OutputFormat) -> Result<(), Ap
 
     print_key_value(vec![
         ("ID", site["id"].as_str().unwrap_or("-").to_string()),
-        ("Status", site["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(site["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Customer ID",
             format_option(&site["your_customer_id"].as_str().map(String::from)),
@@ -151,22 +247,208 @@ pub fn show(client: &ApiClient, id: &str, format: OutputFormat) -> Result<(), Ap
         ("Tags", format_tags(&site["tags"])),
         (
             "Created",
-            format_option(&site["created_at"].as_str().map(String::from)),
+            format_timestamp(&site["created_at"].as_str().map(String::from)),
         ),
         (
             "Updated",
-            format_option(&site["updated_at"].as_str().map(String::from)),
+            format_timestamp(&site["updated_at"].as_str().map(String::from)),
+        ),
+    ]);
+
+    Ok(())
+}
+
+pub fn overview(client: &ApiClient, id: &str, format: OutputFormat) -> Result<(), ApiError> {
+    let (site, envs) = thread::scope(|s| {
+        let envs = s.spawn(|| fetch_environments(client, id));
+        let site = client.get::<Value>(&format!("/api/v1/vector/sites/{}", id));
+        (site, join(envs))
+    });
+    let site = site?["data"].take();
+    let envs = envs?;
+
+    // Two requests per environment (SSL status, then the latest deployment),
+    // CONCURRENCY at a time.
+    let next = AtomicUsize::new(0);
+    type Slot = Option<Result<Value, ApiError>>;
+    let results: Mutex<Vec<(Slot, Slot)>> =
+        Mutex::new((0..envs.len()).map(|_| (None, None)).collect());
+    thread::scope(|s| {
+        for _ in 0..CONCURRENCY.min(envs.len() * 2) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(env) = envs.get(i / 2) else {
+                        break;
+                    };
+                    let env_id = env["id"].as_str().unwrap_or_default();
+                    let ssl = i.is_multiple_of(2);
+                    let result = if ssl {
+                        client.get::<Value>(&format!("/api/v1/vector/environments/{}/ssl", env_id))
+                    } else {
+                        client.get_with_query::<Value, _>(
+                            &format!("/api/v1/vector/environments/{}/deployments", env_id),
+                            &PaginationQuery {
+                                page: 1,
+                                per_page: 1,
+                            },
+                        )
+                    };
+                    let mut results = results.lock().unwrap_or_else(|e| e.into_inner());
+                    let slot = &mut results[i / 2];
+                    if ssl {
+                        slot.0 = Some(result);
+                    } else {
+                        slot.1 = Some(result);
+                    }
+                }
+            });
+        }
+    });
+    let fetched =
+        |slot: Slot| slot.unwrap_or_else(|| Err(ApiError::Other("Not fetched".to_string())));
+    let details: Vec<(Result<Value, ApiError>, Result<Value, ApiError>)> = results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|(ssl, deploys)| (fetched(ssl), fetched(deploys)))
+        .collect();
+
+    // A failing detail request shouldn't hide the rest of the overview.
+    let mut errors: Vec<String> = Vec::new();
+    let mut detail = |env: &Value, what: &str, result: Result<Value, ApiError>| match result {
+        Ok(mut response) => response["data"].take(),
+        Err(ApiError::NotFound(_)) => Value::Null,
+        Err(e) => {
+            errors.push(format!(
+                "{} for {}: {}",
+                what,
+                env["name"].as_str().unwrap_or("-"),
+                e
+            ));
+            Value::Null
+        }
+    };
+    let environments: Vec<Value> = envs
+        .iter()
+        .zip(details)
+        .map(|(env, (ssl, deploys))| {
+            let ssl = detail(env, "SSL status", ssl);
+            let latest = detail(env, "Deployments", deploys)
+                .get_mut(0)
+                .map(Value::take)
+                .unwrap_or_default();
+            json!({
+                "environment": env,
+                "ssl": ssl,
+                "latest_deployment": latest,
+            })
+        })
+        .collect();
+
+    if format == OutputFormat::Json {
+        print_json(&json!({
+            "data": {
+                "site": site,
+                "environments": environments,
+                "errors": errors,
+            }
+        }));
+        return Ok(());
+    }
+
+    print_key_value(vec![
+        ("ID", site["id"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(site["status"].as_str().unwrap_or("-")),
+        ),
+        (
+            "Customer ID",
+            format_option(&site["your_customer_id"].as_str().map(String::from)),
+        ),
+        (
+            "Dev Domain",
+            format_option(&site["dev_domain"].as_str().map(String::from)),
+        ),
+        ("Tags", format_tags(&site["tags"])),
+        (
+            "Created",
+            format_timestamp(&site["created_at"].as_str().map(String::from)),
         ),
     ]);
+    print_message("");
+
+    if environments.is_empty() {
+        print_message("No environments found.");
+    } else {
+        let rows: Vec<Vec<String>> = environments
+            .iter()
+            .map(|e| {
+                let env = &e["environment"];
+                let deploy = &e["latest_deployment"];
+                let domain = env["custom_domain"]
+                    .as_str()
+                    .or_else(|| env["platform_domain"].as_str());
+                vec![
+                    env["name"].as_str().unwrap_or("-").to_string(),
+                    format_status(env["status"].as_str().unwrap_or("-")),
+                    format_bool(env["is_production"].as_bool().unwrap_or(false)),
+                    format_option(&domain.map(String::from)),
+                    format_status(e["ssl"]["status"].as_str().unwrap_or("-")),
+                    format_status(deploy["status"].as_str().unwrap_or("-")),
+                    format_timestamp(&deploy["created_at"].as_str().map(String::from)),
+                ]
+            })
+            .collect();
+
+        print_table(
+            vec![
+                "Environment",
+                "Status",
+                "Production",
+                "Domain",
+                "SSL",
+                "Last Deploy",
+                "Deployed",
+            ],
+            rows,
+        );
+    }
+
+    for error in &errors {
+        eprintln!("Warning: {}", error);
+    }
 
     Ok(())
 }
 
+fn join<T>(handle: thread::ScopedJoinHandle<'_, T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// The ID of a site the API just created, needed to wait for it.
+fn new_site_id(response: &Value, action: &str) -> Result<String, ApiError> {
+    response["data"]["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| {
+            ApiError::Other(format!(
+                "The site was {} but the API did not return its ID, so it cannot be waited for. \
+                 Find it with: vector site list",
+                action
+            ))
+        })
+}
+
 pub fn create(
     client: &ApiClient,
     customer_id: &str,
     dev_php_version: &str,
     tags: Option<Vec<String>>,
+    wait: Option<Wait>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = CreateSiteRequest {
@@ -175,7 +457,11 @@ pub fn create(
         tags,
     };
 
-    let response: Value = client.post("/api/v1/vector/sites", &body)?;
+    let mut response: Value = client.post("/api/v1/vector/sites", &body)?;
+    if let Some(wait) = wait {
+        let id = new_site_id(&response, "created")?;
+        response = wait_for_status(client, &id, "active", wait)?;
+    }
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -186,24 +472,35 @@ pub fn create(
     print_message(&format!(
         "Site created: {} ({})",
         site["id"].as_str().unwrap_or("-"),
-        site["status"].as_str().unwrap_or("-")
+        format_status(site["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
 }
 
+/// Fields `site update` can change.
+const UPDATE_FIELDS: &[Field] = &[("Customer ID", "your_customer_id"), ("Tags", "tags")];
+
 pub fn update(
     client: &ApiClient,
     id: &str,
     customer_id: Option<String>,
     tags: Option<Vec<String>>,
+    unmodified_since: Option<Timestamp>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = UpdateSiteRequest {
         your_customer_id: customer_id,
         tags,
     };
-    let response: Value = client.put(&format!("/api/v1/vector/sites/{}", id), &body)?;
+    let Updated { before, response } = update::put_unmodified(
+        client,
+        &format!("Site {}", id),
+        &format!("/api/v1/vector/sites/{}", id),
+        &body,
+        unmodified_since,
+        UPDATE_FIELDS,
+    )?;
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -211,23 +508,39 @@ pub fn update(
     }
 
     print_message("Site updated successfully.");
+    diff::print_changes(diff::changes(
+        &before["data"],
+        &response["data"],
+        &body,
+        UPDATE_FIELDS,
+        &[],
+    ));
     Ok(())
 }
 
+/// What a confirmation prompt shows about a site.
+pub fn site_details(client: &ApiClient, id: &str) -> Result<Vec<(&'static str, String)>, ApiError> {
+    confirm::fetch_details(
+        client,
+        &format!("/api/v1/vector/sites/{}", id),
+        &[
+            ("ID", "id"),
+            ("Customer ID", "your_customer_id"),
+            ("Dev Domain", "dev_domain"),
+            ("Status", "status"),
+        ],
+    )
+}
+
 pub fn delete(
     client: &ApiClient,
     id: &str,
     force: bool,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
-    if !force {
-        eprint!("Are you sure you want to delete site {}? [y/N] ", id);
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input).ok();
-        if !input.trim().eq_ignore_ascii_case("y") {
-            print_message("Aborted.");
-            return Ok(());
-        }
+    if !force && !confirm::confirm(&format!("delete site {}", id), || site_details(client, id))? {
+        print_message("Aborted.");
+        return Ok(());
     }
 
     let response: Value = client.delete(&format!("/api/v1/vector/sites/{}", id))?;
@@ -247,6 +560,7 @@ pub fn clone(
     customer_id: Option<String>,
     dev_php_version: Option<String>,
     tags: Option<Vec<String>>,
+    wait: Option<Wait>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = CloneSiteRequest {
@@ -255,7 +569,11 @@ pub fn clone(
         tags,
     };
 
-    let response: Value = client.post(&format!("/api/v1/vector/sites/{}/clone", id), &body)?;
+    let mut response: Value = client.post(&format!("/api/v1/vector/sites/{}/clone", id), &body)?;
+    if let Some(wait) = wait {
+        let clone_id = new_site_id(&response, "cloned")?;
+        response = wait_for_status(client, &clone_id, "active", wait)?;
+    }
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -266,33 +584,51 @@ pub fn clone(
     print_message(&format!(
         "Site clone initiated: {} ({})",
         site["id"].as_str().unwrap_or("-"),
-        site["status"].as_str().unwrap_or("-")
+        format_status(site["status"].as_str().unwrap_or("-"))
     ));
 
     Ok(())
 }
 
-pub fn suspend(client: &ApiClient, id: &str, format: OutputFormat) -> Result<(), ApiError> {
-    let response: Value = client.put_empty(&format!("/api/v1/vector/sites/{}/suspend", id))?;
+pub fn suspend(
+    client: &ApiClient,
+    id: &str,
+    wait: Option<Wait>,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let response = SiteAction::Suspend.run(client, id, wait)?;
 
     if format == OutputFormat::Json {
         print_json(&response);
         return Ok(());
     }
 
-    print_message("Site suspension initiated.");
+    if wait.is_some() {
+        print_message("Site suspended.");
+    } else {
+        print_message("Site suspension initiated.");
+    }
     Ok(())
 }
 
-pub fn unsuspend(client: &ApiClient, id: &str, format: OutputFormat) -> Result<(), ApiError> {
-    let response: Value = client.put_empty(&format!("/api/v1/vector/sites/{}/unsuspend", id))?;
+pub fn unsuspend(
+    client: &ApiClient,
+    id: &str,
+    wait: Option<Wait>,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let response = SiteAction::Unsuspend.run(client, id, wait)?;
 
     if format == OutputFormat::Json {
         print_json(&response);
         return Ok(());
     }
 
-    print_message("Site unsuspension initiated.");
+    if wait.is_some() {
+        print_message("Site unsuspended.");
+    } else {
+        print_message("Site unsuspension initiated.");
+    }
     Ok(())
 }
 
@@ -390,9 +726,7 @@ pub fn purge_cache(
     url: Option<String>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
-    let body = PurgeCacheRequest { cache_tag, url };
-    let response: Value =
-        client.post(&format!("/api/v1/vector/sites/{}/purge-cache", id), &body)?;
+    let response = SiteAction::PurgeCache { cache_tag, url }.run(client, id, None)?;
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -403,84 +737,394 @@ pub fn purge_cache(
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn logs(
+pub fn wp_reconfig(
     client: &ApiClient,
     id: &str,
-    start_time: Option<String>,
-    end_time: Option<String>,
-    limit: Option<u32>,
-    environment: Option<String>,
-    deployment_id: Option<String>,
-    level: Option<String>,
-    cursor: Option<String>,
+    wait: Option<Wait>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
-    let query = LogsQuery {
-        start_time,
-        end_time,
-        limit,
-        environment,
-        deployment_id,
-        level,
-        cursor,
-    };
-    let response: Value =
-        client.get_with_query(&format!("/api/v1/vector/sites/{}/logs", id), &query)?;
+    let response = SiteAction::WpReconfig.run(client, id, wait)?;
 
     if format == OutputFormat::Json {
         print_json(&response);
         return Ok(());
     }
 
-    // Parse the Axiom-style log format
-    if let Some(tables) = response["data"]["logs"]["tables"].as_array() {
-        for table in tables {
-            if let Some(rows) = table["rows"].as_array() {
-                for row in rows {
-                    if let Some(row_arr) = row.as_array() {
-                        // Typically: [timestamp, message, level]
-                        let parts: Vec<String> = row_arr
-                            .iter()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect();
-                        if !parts.is_empty() {
-                            println!("{}", parts.join(" | "));
-                        }
+    print_message("wp-config.php regenerated successfully.");
+    Ok(())
+}
+
+/// Applies `action` to every selected site, `concurrency` at a time, and
+/// reports the outcome per site. Asks first, since a selection can cover
+/// the whole fleet.
+pub fn bulk(
+    client: &ApiClient,
+    selection: Selection,
+    action: SiteAction,
+    wait: Option<Wait>,
+    concurrency: usize,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    confirm::ensure_confirmable(&format!("{} the selected sites", action.verb()))?;
+    let ids = select(client, &selection)?;
+    if ids.is_empty() {
+        return Err(ApiError::ValidationError(
+            "No sites matched the selection".to_string(),
+        ));
+    }
+    let targets = match ids.as_slice() {
+        [id] => format!("site {}", id),
+        _ => format!("{} sites", ids.len()),
+    };
+    if !confirm::confirm(&format!("{} {}", action.verb(), targets), || {
+        Ok(vec![
+            ("Sites", ids.len().to_string()),
+            ("IDs", ids.join(", ")),
+        ])
+    })? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
+    let progress = atty::is(atty::Stream::Stderr);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<(), String>>>> = Mutex::new(vec![None; ids.len()]);
+
+    thread::scope(|s| {
+        for _ in 0..concurrency.clamp(1, ids.len()) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(id) = ids.get(i) else {
+                        break;
+                    };
+                    let outcome = action
+                        .run(client, id, wait)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string());
+                    results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(outcome);
+
+                    let finished = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    if progress {
+                        eprint!("\rProcessed {} of {} sites...", finished, ids.len());
                     }
                 }
-            }
+            });
         }
+    });
+    if progress {
+        eprintln!();
+    }
 
-        // Show pagination info if there are more results
-        if response["data"]["has_more"].as_bool().unwrap_or(false)
-            && let Some(next_cursor) = response["data"]["cursor"].as_str()
-        {
-            eprintln!();
-            eprintln!(
-                "More results available. Use --cursor {} to continue.",
-                next_cursor
-            );
-        }
+    let results: Vec<Result<(), String>> = results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err("Not processed".to_string())))
+        .collect();
+    let failures = results.iter().filter(|r| r.is_err()).count();
+
+    if format == OutputFormat::Json {
+        let data: Vec<Value> = ids
+            .iter()
+            .zip(&results)
+            .map(|(id, result)| match result {
+                Ok(()) => json!({"id": id, "success": true}),
+                Err(e) => json!({"id": id, "success": false, "error": e}),
+            })
+            .collect();
+        print_json(&json!({ "data": data }));
     } else {
-        print_message("No logs available.");
+        let rows: Vec<Vec<String>> = ids
+            .iter()
+            .zip(&results)
+            .map(|(id, result)| {
+                vec![
+                    id.clone(),
+                    match result {
+                        Ok(()) => format_status("success"),
+                        Err(e) => format!("{}: {}", format_status("failed"), e),
+                    },
+                ]
+            })
+            .collect();
+        print_table(vec!["Site", "Result"], rows);
+        print_message(&format!(
+            "{} succeeded, {} failed.",
+            ids.len() - failures,
+            failures
+        ));
     }
 
+    if failures > 0 {
+        return Err(ApiError::Other(format!(
+            "{} of {} sites failed",
+            failures,
+            ids.len()
+        )));
+    }
     Ok(())
 }
 
-pub fn wp_reconfig(client: &ApiClient, id: &str, format: OutputFormat) -> Result<(), ApiError> {
-    let response: Value = client.post_empty(&format!("/api/v1/vector/sites/{}/wp/reconfig", id))?;
+pub fn select(client: &ApiClient, selection: &Selection) -> Result<Vec<String>, ApiError> {
+    if selection.stdin {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| ApiError::Other(format!("Failed to read site IDs from stdin: {}", e)))?;
+        return Ok(input.split_whitespace().map(String::from).collect());
+    }
+
+    let sites = fetch_all(client)?;
+    Ok(sites
+        .iter()
+        .filter(|site| {
+            selection.tag.as_ref().is_none_or(|tag| {
+                site["tags"]
+                    .as_array()
+                    .is_some_and(|tags| tags.iter().any(|t| t.as_str() == Some(tag)))
+            })
+        })
+        .filter(|site| {
+            selection
+                .customer_id
+                .as_ref()
+                .is_none_or(|customer| site["your_customer_id"].as_str() == Some(customer))
+        })
+        .filter_map(|site| site["id"].as_str().map(String::from))
+        .collect())
+}
+
+pub fn wait(
+    client: &ApiClient,
+    id: &str,
+    status: &str,
+    wait: Wait,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let response = wait_for_status(client, id, status, wait)?;
 
     if format == OutputFormat::Json {
         print_json(&response);
         return Ok(());
     }
 
-    print_message("wp-config.php regenerated successfully.");
+    print_message(&format!("Site {} is {}.", id, format_status(status)));
     Ok(())
 }
 
+/// Polls the site until it reaches `target`, returning the final site
+/// response. Fails early if the site lands in a failed state.
+pub fn wait_for_status(
+    client: &ApiClient,
+    id: &str,
+    target: &str,
+    wait: Wait,
+) -> Result<Value, ApiError> {
+    poll_status(
+        || client.get(&format!("/api/v1/vector/sites/{}", id)),
+        id,
+        target,
+        None,
+        wait,
+    )
+}
+
+/// Like `wait_for_status`, for an action started on the site as it was in
+/// `before`. A site already in `target` must first be seen changing (its
+/// status or `updated_at`), so the wait covers the action's transition.
+pub fn wait_for_change(
+    client: &ApiClient,
+    id: &str,
+    target: &str,
+    before: &Value,
+    wait: Wait,
+) -> Result<Value, ApiError> {
+    poll_status(
+        || client.get(&format!("/api/v1/vector/sites/{}", id)),
+        id,
+        target,
+        Some(before),
+        wait,
+    )
+}
+
+fn poll_status(
+    mut fetch: impl FnMut() -> Result<Value, ApiError>,
+    id: &str,
+    target: &str,
+    before: Option<&Value>,
+    wait: Wait,
+) -> Result<Value, ApiError> {
+    let started = Instant::now();
+    let progress = atty::is(atty::Stream::Stderr);
+    let mut changed = before.is_none_or(|before| {
+        !before["status"]
+            .as_str()
+            .unwrap_or("-")
+            .eq_ignore_ascii_case(target)
+    });
+
+    loop {
+        let response: Value = fetch()?;
+        let status = response["data"]["status"].as_str().unwrap_or("-");
+        changed = changed
+            || before.is_some_and(|before| {
+                before["status"] != response["data"]["status"]
+                    || before["updated_at"] != response["data"]["updated_at"]
+            });
+
+        if changed && status.eq_ignore_ascii_case(target) {
+            if progress {
+                eprintln!();
+            }
+            return Ok(response);
+        }
+        if is_failed_status(status) {
+            if progress {
+                eprintln!();
+            }
+            return Err(ApiError::Other(format!(
+                "Site {} is {} (waiting for {})",
+                id, status, target
+            )));
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed >= wait.timeout {
+            if progress {
+                eprintln!();
+            }
+            if !changed {
+                return Err(ApiError::Timeout(format!(
+                    "site {} showed no change after {}s (still {})",
+                    id,
+                    wait.timeout.as_secs(),
+                    status
+                )));
+            }
+            return Err(ApiError::Timeout(format!(
+                "site {} is still {} after {}s (waiting for {})",
+                id,
+                status,
+                wait.timeout.as_secs(),
+                target
+            )));
+        }
+
+        if progress {
+            eprint!(
+                "\rWaiting for site {} to become {} (currently {}, {}s)...",
+                id,
+                target,
+                status,
+                elapsed.as_secs()
+            );
+        }
+        thread::sleep(wait.interval.min(wait.timeout - elapsed));
+    }
+}
+
+fn is_failed_status(status: &str) -> bool {
+    let status = status.to_ascii_lowercase();
+    matches!(status.as_str(), "failed" | "failure" | "error") || status.ends_with("_failed")
+}
+
+pub fn tag(client: &ApiClient, request: TagRequest, format: OutputFormat) -> Result<(), ApiError> {
+    request.validate()?;
+    let ids: Vec<String> = match &request.matching_tag {
+        Some(tag) => select(
+            client,
+            &Selection {
+                tag: Some(tag.clone()),
+                ..Default::default()
+            },
+        )?,
+        None => request.ids(),
+    };
+
+    tags::update(client, "/api/v1/vector/sites", &ids, &request, format)
+}
+
+/// Every site on the account, following pagination.
+pub fn fetch_all(client: &ApiClient) -> Result<Vec<Value>, ApiError> {
+    fetch_all_pages(client, "/api/v1/vector/sites")
+}
+
+/// Collects every item of a paginated listing.
+pub fn fetch_all_pages(client: &ApiClient, path: &str) -> Result<Vec<Value>, ApiError> {
+    fetch_all_pages_filtered(client, path, &())
+}
+
+/// Every environment of site `id`, across all pages.
+pub fn fetch_environments(client: &ApiClient, id: &str) -> Result<Vec<Value>, ApiError> {
+    fetch_all_pages_filtered(
+        client,
+        "/api/v1/vector/environments",
+        &EnvironmentsFilter { site: id },
+    )
+}
+
+/// Every environment of every site. The environments listing is per site,
+/// so this lists the sites and then their environments, CONCURRENCY sites
+/// at a time.
+pub fn fetch_all_environments(client: &ApiClient) -> Result<Vec<Value>, ApiError> {
+    let sites = fetch_all_pages(client, "/api/v1/vector/sites")?;
+    let next = AtomicUsize::new(0);
+    type Slot = Option<Result<Vec<Value>, ApiError>>;
+    let results: Mutex<Vec<Slot>> = Mutex::new((0..sites.len()).map(|_| None).collect());
+    thread::scope(|s| {
+        for _ in 0..CONCURRENCY.min(sites.len()) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(site) = sites.get(i) else {
+                        break;
+                    };
+                    let result =
+                        fetch_environments(client, site["id"].as_str().unwrap_or_default());
+                    results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+                }
+            });
+        }
+    });
+
+    let mut environments = Vec::new();
+    for result in results.into_inner().unwrap_or_else(|e| e.into_inner()) {
+        environments
+            .extend(result.unwrap_or_else(|| Err(ApiError::Other("Not fetched".to_string())))?);
+    }
+    Ok(environments)
+}
+
+/// Collects every item of a paginated listing narrowed by `filter`, which
+/// is sent as query parameters with each page.
+fn fetch_all_pages_filtered<F: Serialize>(
+    client: &ApiClient,
+    path: &str,
+    filter: &F,
+) -> Result<Vec<Value>, ApiError> {
+    let mut items = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let query = PageQuery {
+            filter,
+            page,
+            per_page: 100,
+        };
+        let response: Value = client.get_with_query(path, &query)?;
+        if let Some(data) = response["data"].as_array() {
+            items.extend(data.iter().cloned());
+        }
+        match extract_pagination(&response) {
+            Some((current, last, _)) if current < last => page += 1,
+            _ => return Ok(items),
+        }
+    }
+}
+
 // SSH Key subcommands
 
 pub fn ssh_key_list(
@@ -516,8 +1160,8 @@ pub fn ssh_key_list(
             vec![
                 k["id"].as_str().unwrap_or("-").to_string(),
                 k["name"].as_str().unwrap_or("-").to_string(),
-                format_option(&k["fingerprint"].as_str().map(String::from)),
-                format_option(&k["created_at"].as_str().map(String::from)),
+                sshkey::display_fingerprint(k),
+                format_timestamp(&k["created_at"].as_str().map(String::from)),
             ]
         })
         .collect();
@@ -567,6 +1211,18 @@ pub fn ssh_key_remove(
     key_id: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(
+        &format!("remove SSH key {} from site {}", key_id, site_id),
+        || {
+            let mut details = site_details(client, site_id)?;
+            details.push(("SSH Key", key_id.to_string()));
+            Ok(details)
+        },
+    )? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!(
         "/api/v1/vector/sites/{}/ssh-keys/{}",
         site_id, key_id
@@ -595,3 +1251,86 @@ fn format_tags(value: &Value) -> String {
         "-".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stub(sites: Vec<Value>) -> impl FnMut() -> Result<Value, ApiError> {
+        let mut sites = sites.into_iter();
+        move || {
+            sites
+                .next()
+                .map(|site| json!({ "data": site }))
+                .ok_or_else(|| ApiError::Other("polled too often".to_string()))
+        }
+    }
+
+    const NO_DELAY: Wait = Wait {
+        timeout: Duration::from_secs(5),
+        interval: Duration::ZERO,
+    };
+
+    #[test]
+    fn test_poll_status_waits_for_transition() {
+        let before = json!({"status": "active", "updated_at": "2026-10-18T10:00:00Z"});
+        let sites = vec![
+            before.clone(),
+            json!({"status": "reconfiguring", "updated_at": "2026-10-18T10:00:01Z"}),
+            json!({"status": "active", "updated_at": "2026-10-18T10:00:05Z", "n": 3}),
+        ];
+        let site = poll_status(stub(sites), "s1", "active", Some(&before), NO_DELAY).unwrap();
+        assert_eq!(site["data"]["n"], 3);
+    }
+
+    #[test]
+    fn test_poll_status_accepts_updated_site() {
+        // A transition too quick to observe still updates the site.
+        let before = json!({"status": "active", "updated_at": "2026-10-18T10:00:00Z"});
+        let sites = vec![json!({"status": "active", "updated_at": "2026-10-18T10:00:02Z"})];
+        assert!(poll_status(stub(sites), "s1", "active", Some(&before), NO_DELAY).is_ok());
+    }
+
+    #[test]
+    fn test_poll_status_without_baseline() {
+        let sites = vec![
+            json!({"status": "provisioning"}),
+            json!({"status": "active"}),
+        ];
+        let site = poll_status(stub(sites), "s1", "active", None, NO_DELAY).unwrap();
+        assert_eq!(site["data"]["status"], "active");
+
+        let sites = vec![
+            json!({"status": "provisioning"}),
+            json!({"status": "failed"}),
+        ];
+        let err = poll_status(stub(sites), "s1", "active", None, NO_DELAY).unwrap_err();
+        assert!(err.to_string().contains("failed"));
+    }
+
+    #[test]
+    fn test_poll_status_times_out_without_change() {
+        let before = json!({"status": "active", "updated_at": "2026-10-18T10:00:00Z"});
+        let wait = Wait {
+            timeout: Duration::ZERO,
+            interval: Duration::ZERO,
+        };
+        let err = poll_status(
+            stub(vec![before.clone()]),
+            "s1",
+            "active",
+            Some(&before),
+            wait,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ApiError::Timeout(_)));
+    }
+
+    #[test]
+    fn test_is_failed_status() {
+        assert!(is_failed_status("failed"));
+        assert!(is_failed_status("PROVISIONING_FAILED"));
+        assert!(!is_failed_status("provisioning"));
+        assert!(!is_failed_status("suspended"));
+    }
+}
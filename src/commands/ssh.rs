@@ -0,0 +1,408 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::api::{ApiClient, ApiError};
+use crate::commands::site::{self, Selection};
+use crate::output::{OutputFormat, print_json, print_message, print_table, write_atomic};
+
+/// Local programs that can be launched against a site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Ssh,
+    Sftp,
+}
+
+/// Where to connect to a site over SSH and SFTP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Endpoint {
+    hostname: String,
+    port: u64,
+    username: String,
+}
+
+impl Endpoint {
+    fn from_site(id: &str, site: &Value) -> Result<Self, ApiError> {
+        let sftp = &site["dev_sftp"];
+        match (sftp["hostname"].as_str(), sftp["username"].as_str()) {
+            (Some(hostname), Some(username)) => Ok(Self {
+                hostname: hostname.to_string(),
+                port: sftp["port"].as_u64().unwrap_or(22),
+                username: username.to_string(),
+            }),
+            _ => Err(ApiError::Other(format!(
+                "Site {} has no SSH access details",
+                id
+            ))),
+        }
+    }
+}
+
+fn fetch_endpoint(client: &ApiClient, id: &str) -> Result<Endpoint, ApiError> {
+    let response: Value = client.get(&format!("/api/v1/vector/sites/{}", id))?;
+    Endpoint::from_site(id, &response["data"])
+}
+
+/// Fetches the endpoints of `ids`, `concurrency` sites at a time, failing
+/// with the first error in `ids` order.
+fn fetch_endpoints(
+    client: &ApiClient,
+    ids: &[String],
+    concurrency: usize,
+) -> Result<Vec<Endpoint>, ApiError> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<Endpoint, ApiError>>>> =
+        Mutex::new((0..ids.len()).map(|_| None).collect());
+
+    thread::scope(|s| {
+        for _ in 0..concurrency.clamp(1, ids.len().max(1)) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(id) = ids.get(i) else {
+                        break;
+                    };
+                    let endpoint = fetch_endpoint(client, id);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(endpoint);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err(ApiError::Other("Not fetched".to_string()))))
+        .collect()
+}
+
+/// Runs the local `ssh` or `sftp` client against the site and exits with
+/// its status. `args` are passed through after the connection options.
+pub fn connect(client: &ApiClient, id: &str, tool: Tool, args: &[String]) -> Result<(), ApiError> {
+    let endpoint = fetch_endpoint(client, id)?;
+    let (program, port_flag) = match tool {
+        Tool::Ssh => ("ssh", "-p"),
+        Tool::Sftp => ("sftp", "-P"),
+    };
+
+    let status = Command::new(program)
+        .arg(port_flag)
+        .arg(endpoint.port.to_string())
+        .arg(format!("{}@{}", endpoint.username, endpoint.hostname))
+        .args(args)
+        .status()
+        .map_err(|e| ApiError::Other(format!("Failed to run {}: {}", program, e)))?;
+
+    if !status.success() {
+        // The client has already reported the problem.
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Adds or replaces a managed `Host vector-<id>` block for each selected
+/// site in the SSH config file, or prints the blocks with `print_only`.
+pub fn config(
+    client: &ApiClient,
+    ids: Vec<String>,
+    selection: Option<Selection>,
+    concurrency: usize,
+    file: Option<PathBuf>,
+    print_only: bool,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let ids = match selection {
+        Some(selection) => site::select(client, &selection)?,
+        None => ids,
+    };
+    if ids.is_empty() {
+        return Err(ApiError::ValidationError(
+            "No sites matched the selection".to_string(),
+        ));
+    }
+
+    let hosts: Vec<(String, Endpoint)> = ids
+        .iter()
+        .cloned()
+        .zip(fetch_endpoints(client, &ids, concurrency)?)
+        .collect();
+    let blocks: Vec<(String, String)> = hosts
+        .iter()
+        .map(|(id, endpoint)| (id.clone(), host_block(id, endpoint)))
+        .collect();
+
+    if print_only {
+        let text: Vec<&str> = blocks.iter().map(|(_, block)| block.as_str()).collect();
+        print_message(text.join("\n").trim_end());
+        return Ok(());
+    }
+
+    let path = match file {
+        Some(path) => path,
+        None => dirs::home_dir()
+            .ok_or_else(|| ApiError::ConfigError("Could not determine home directory".into()))?
+            .join(".ssh")
+            .join("config"),
+    };
+    write_config(&path, &blocks)?;
+
+    if format == OutputFormat::Json {
+        let data: Vec<Value> = hosts
+            .iter()
+            .map(|(id, e)| {
+                json!({
+                    "id": id,
+                    "host": host_alias(id),
+                    "hostname": e.hostname,
+                    "port": e.port,
+                    "user": e.username,
+                })
+            })
+            .collect();
+        print_json(&json!({"data": {"path": path.to_string_lossy(), "hosts": data}}));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = hosts
+        .iter()
+        .map(|(id, e)| {
+            vec![
+                host_alias(id),
+                e.hostname.clone(),
+                e.port.to_string(),
+                e.username.clone(),
+            ]
+        })
+        .collect();
+    print_table(vec!["Host", "HostName", "Port", "User"], rows);
+    print_message(&format!(
+        "Updated {}. Connect with: ssh {}",
+        path.display(),
+        host_alias(&ids[0])
+    ));
+    Ok(())
+}
+
+fn host_alias(id: &str) -> String {
+    format!("vector-{}", id)
+}
+
+fn begin_marker(id: &str) -> String {
+    format!("# BEGIN vector site {}", id)
+}
+
+fn end_marker(id: &str) -> String {
+    format!("# END vector site {}", id)
+}
+
+fn host_block(id: &str, endpoint: &Endpoint) -> String {
+    format!(
+        "{}\nHost {}\n    HostName {}\n    Port {}\n    User {}\n{}\n",
+        begin_marker(id),
+        host_alias(id),
+        endpoint.hostname,
+        endpoint.port,
+        endpoint.username,
+        end_marker(id)
+    )
+}
+
+fn write_config(path: &Path, blocks: &[(String, String)]) -> Result<(), ApiError> {
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(ApiError::ConfigError(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )));
+        }
+    };
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent).map_err(|e| {
+            ApiError::ConfigError(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    let merged = merge_blocks(path, &existing, blocks)?;
+    write_atomic(path, merged.as_bytes(), true)
+}
+
+/// Whether `line` starts a section that applies to every host (`Host *` or
+/// `Match all`). ssh uses the first value it finds for each option, so site
+/// blocks have to come before these.
+fn is_catch_all(line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    let keyword = words.next().unwrap_or_default().to_ascii_lowercase();
+    let rest: Vec<&str> = words.collect();
+    match keyword.as_str() {
+        "host" => rest == ["*"],
+        "match" => rest.len() == 1 && rest[0].eq_ignore_ascii_case("all"),
+        _ => false,
+    }
+}
+
+/// Replaces each site's managed block in place and inserts blocks for sites
+/// that are not in the file yet before the first catch-all section (or at
+/// the end). Everything else is left untouched. Fails without changing
+/// anything when a block being replaced has no END marker.
+fn merge_blocks(
+    path: &Path,
+    existing: &str,
+    blocks: &[(String, String)],
+) -> Result<String, ApiError> {
+    let present: Vec<bool> = blocks
+        .iter()
+        .map(|(id, _)| existing.lines().any(|line| line.trim() == begin_marker(id)))
+        .collect();
+    let unterminated = |id: &str| {
+        ApiError::ConfigError(format!(
+            "{} has \"{}\" without a matching \"{}\" line. Fix the file and retry.",
+            path.display(),
+            begin_marker(id),
+            end_marker(id)
+        ))
+    };
+
+    let mut output = String::new();
+    let mut placed = vec![false; blocks.len()];
+    let mut inserted = false;
+    let mut skipping: Option<&str> = None;
+
+    for line in existing.lines() {
+        if let Some(id) = skipping {
+            if line.trim() == end_marker(id) {
+                skipping = None;
+            } else if line.trim().starts_with(&begin_marker("")) {
+                return Err(unterminated(id));
+            }
+            continue;
+        }
+        if !inserted && is_catch_all(line) {
+            for (i, (_, block)) in blocks.iter().enumerate() {
+                if !present[i] {
+                    output.push_str(block);
+                    output.push('\n');
+                }
+            }
+            inserted = true;
+        }
+        let found = blocks
+            .iter()
+            .position(|(id, _)| line.trim() == begin_marker(id));
+        match found {
+            Some(i) => {
+                if !placed[i] {
+                    output.push_str(&blocks[i].1);
+                    placed[i] = true;
+                }
+                skipping = Some(&blocks[i].0);
+            }
+            None => {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+    }
+    if let Some(id) = skipping {
+        return Err(unterminated(id));
+    }
+
+    if !inserted {
+        for (i, (_, block)) in blocks.iter().enumerate() {
+            if present[i] {
+                continue;
+            }
+            if !output.is_empty() && !output.ends_with("\n\n") {
+                output.push('\n');
+            }
+            output.push_str(block);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(hostname: &str) -> Endpoint {
+        Endpoint {
+            hostname: hostname.to_string(),
+            port: 2222,
+            username: "site_user".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_from_site() {
+        let site = json!({"dev_sftp": {"hostname": "ssh.example.com", "port": 2222, "username": "site_user"}});
+        assert_eq!(
+            Endpoint::from_site("s1", &site).unwrap(),
+            endpoint("ssh.example.com")
+        );
+        assert!(Endpoint::from_site("s1", &json!({"id": "s1"})).is_err());
+    }
+
+    #[test]
+    fn test_merge_blocks() {
+        let old = host_block("s1", &endpoint("old.example.com"));
+        let existing = format!(
+            "Host github.com\n    User git\n\n{}\nHost *\n    ForwardAgent no\n",
+            old
+        );
+        let blocks = vec![
+            (
+                "s1".to_string(),
+                host_block("s1", &endpoint("new.example.com")),
+            ),
+            (
+                "s2".to_string(),
+                host_block("s2", &endpoint("s2.example.com")),
+            ),
+        ];
+
+        let path = Path::new("config");
+        let merged = merge_blocks(path, &existing, &blocks).unwrap();
+        assert!(!merged.contains("old.example.com"));
+        assert!(merged.starts_with("Host github.com\n    User git\n\n# BEGIN vector site s1\n"));
+        assert!(merged.contains("    HostName new.example.com\n"));
+        // New blocks go before `Host *` so its options do not override them.
+        assert!(merged.ends_with(&format!("{}\nHost *\n    ForwardAgent no\n", blocks[1].1)));
+
+        // Re-running with the same blocks changes nothing.
+        assert_eq!(merge_blocks(path, &merged, &blocks).unwrap(), merged);
+
+        let appended = merge_blocks(path, "Host github.com\n    User git\n", &blocks[1..]).unwrap();
+        assert_eq!(
+            appended,
+            format!("Host github.com\n    User git\n\n{}", blocks[1].1)
+        );
+    }
+
+    #[test]
+    fn test_merge_blocks_rejects_truncated_block() {
+        let blocks = vec![(
+            "s1".to_string(),
+            host_block("s1", &endpoint("new.example.com")),
+        )];
+        let path = Path::new("config");
+        let truncated = "# BEGIN vector site s1\nHost vector-s1\n\nHost github.com\n    User git\n";
+        let err = merge_blocks(path, truncated, &blocks).unwrap_err();
+        assert!(err.to_string().contains("# END vector site s1"));
+
+        let followed = format!(
+            "# BEGIN vector site s1\nHost vector-s1\n{}",
+            host_block("s2", &endpoint("s2.example.com"))
+        );
+        assert!(merge_blocks(path, &followed, &blocks).is_err());
+    }
+}
@@ -0,0 +1,438 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::api::{ApiClient, ApiError};
+use crate::commands::site;
+use crate::confirm;
+use crate::output::{OutputFormat, format_status, print_json, print_message, print_table};
+use crate::sshkey;
+
+/// Sites whose keys are fetched at once.
+const CONCURRENCY: usize = 4;
+
+/// Where a key is installed: on the account, or on a site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Location {
+    Account,
+    Site(String),
+}
+
+impl Location {
+    fn label(&self) -> String {
+        match self {
+            Location::Account => "account".to_string(),
+            Location::Site(id) => format!("site {}", id),
+        }
+    }
+}
+
+/// One installed copy of a key.
+#[derive(Debug, Clone)]
+struct Installed {
+    location: Location,
+    key_id: String,
+    name: String,
+}
+
+/// Everything the audit found for one fingerprint.
+#[derive(Debug, Default)]
+struct KeyGroup {
+    installs: Vec<Installed>,
+}
+
+impl KeyGroup {
+    fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.installs.iter().map(|i| i.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    fn on_account(&self) -> bool {
+        self.installs
+            .iter()
+            .any(|i| i.location == Location::Account)
+    }
+
+    fn sites(&self) -> Vec<&str> {
+        let mut sites: Vec<&str> = self
+            .installs
+            .iter()
+            .filter_map(|i| match &i.location {
+                Location::Site(id) => Some(id.as_str()),
+                Location::Account => None,
+            })
+            .collect();
+        sites.dedup();
+        sites
+    }
+}
+
+/// How widely a key is installed across the audited sites.
+fn coverage(on_sites: usize, total_sites: usize) -> &'static str {
+    match on_sites {
+        0 => "none",
+        n if n == total_sites => "complete",
+        _ => "partial",
+    }
+}
+
+/// Strips the `SHA256:` prefix so fingerprints compare either way.
+fn normalize_fingerprint(fingerprint: &str) -> &str {
+    fingerprint.trim_start_matches("SHA256:")
+}
+
+fn group_keys(installs: Vec<(String, Installed)>) -> BTreeMap<String, KeyGroup> {
+    let mut groups: BTreeMap<String, KeyGroup> = BTreeMap::new();
+    for (fingerprint, install) in installs {
+        groups
+            .entry(fingerprint)
+            .or_default()
+            .installs
+            .push(install);
+    }
+    groups
+}
+
+fn installed(location: Location, key: &Value) -> (String, Installed) {
+    (
+        sshkey::display_fingerprint(key),
+        Installed {
+            location,
+            key_id: key["id"].as_str().unwrap_or("-").to_string(),
+            name: key["name"].as_str().unwrap_or("-").to_string(),
+        },
+    )
+}
+
+/// Keys for one site, or why they could not be read.
+type SiteKeys = Option<Result<Vec<Value>, ApiError>>;
+
+/// Keys grouped by fingerprint, along with the sites whose keys were read
+/// and errors for those that could not be.
+struct Audit {
+    groups: BTreeMap<String, KeyGroup>,
+    site_ids: Vec<String>,
+    errors: Vec<String>,
+}
+
+fn gather(client: &ApiClient) -> Result<Audit, ApiError> {
+    let account_keys = site::fetch_all_pages(client, "/api/v1/vector/ssh-keys")?;
+    let site_ids: Vec<String> = site::fetch_all(client)?
+        .iter()
+        .filter_map(|s| s["id"].as_str().map(String::from))
+        .collect();
+
+    let mut installs: Vec<(String, Installed)> = account_keys
+        .iter()
+        .map(|key| installed(Location::Account, key))
+        .collect();
+
+    let progress = atty::is(atty::Stream::Stderr);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let site_keys: Mutex<Vec<SiteKeys>> = Mutex::new((0..site_ids.len()).map(|_| None).collect());
+
+    thread::scope(|s| {
+        for _ in 0..CONCURRENCY.min(site_ids.len()) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(id) = site_ids.get(i) else {
+                        break;
+                    };
+                    let keys = site::fetch_all_pages(
+                        client,
+                        &format!("/api/v1/vector/sites/{}/ssh-keys", id),
+                    );
+                    site_keys.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(keys);
+
+                    let finished = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    if progress {
+                        eprint!("\rChecked {} of {} sites...", finished, site_ids.len());
+                    }
+                }
+            });
+        }
+    });
+    if progress && !site_ids.is_empty() {
+        eprintln!();
+    }
+
+    let mut errors = Vec::new();
+    let mut audited = Vec::new();
+    let site_keys = site_keys.into_inner().unwrap_or_else(|e| e.into_inner());
+    for (id, keys) in site_ids.iter().zip(site_keys) {
+        match keys {
+            Some(Ok(keys)) => {
+                installs.extend(
+                    keys.iter()
+                        .map(|key| installed(Location::Site(id.clone()), key)),
+                );
+                audited.push(id.clone());
+            }
+            Some(Err(e)) => errors.push(format!("site {}: {}", id, e)),
+            None => errors.push(format!("site {}: not checked", id)),
+        }
+    }
+
+    for error in &errors {
+        eprintln!("Warning: could not read SSH keys for {}", error);
+    }
+
+    Ok(Audit {
+        groups: group_keys(installs),
+        site_ids: audited,
+        errors,
+    })
+}
+
+/// Lists every SSH key on the account and its sites, grouped by
+/// fingerprint, so keys installed on only some sites stand out.
+pub fn audit(client: &ApiClient, format: OutputFormat) -> Result<(), ApiError> {
+    let Audit {
+        groups,
+        site_ids,
+        errors,
+    } = gather(client)?;
+
+    if format == OutputFormat::Json {
+        let data: Vec<Value> = groups
+            .iter()
+            .map(|(fingerprint, group)| {
+                let sites = group.sites();
+                let missing: Vec<&str> = site_ids
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|id| !sites.contains(id))
+                    .collect();
+                json!({
+                    "fingerprint": fingerprint,
+                    "names": group.names(),
+                    "account": group.on_account(),
+                    "coverage": coverage(sites.len(), site_ids.len()),
+                    "sites": sites,
+                    "missing_sites": missing,
+                    "keys": group.installs.iter().map(|i| json!({
+                        "location": i.location.label(),
+                        "id": i.key_id,
+                        "name": i.name,
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        print_json(&json!({"data": data, "sites": site_ids.len(), "errors": errors}));
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        print_message("No SSH keys found.");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = groups
+        .iter()
+        .map(|(fingerprint, group)| {
+            let sites = group.sites();
+            let coverage = coverage(sites.len(), site_ids.len());
+            vec![
+                fingerprint.clone(),
+                group.names().join(", "),
+                if group.on_account() { "yes" } else { "no" }.to_string(),
+                format!("{}/{}", sites.len(), site_ids.len()),
+                format_status(coverage),
+                if coverage == "partial" {
+                    sites.join(", ")
+                } else {
+                    "-".to_string()
+                },
+            ]
+        })
+        .collect();
+    print_table(
+        vec![
+            "Fingerprint",
+            "Names",
+            "Account",
+            "Sites",
+            "Coverage",
+            "On Sites",
+        ],
+        rows,
+    );
+
+    let partial = groups
+        .values()
+        .filter(|g| coverage(g.sites().len(), site_ids.len()) == "partial")
+        .count();
+    print_message(&format!(
+        "{} distinct keys across the account and {} sites; {} installed on only some sites.",
+        groups.len(),
+        site_ids.len(),
+        partial
+    ));
+    Ok(())
+}
+
+/// Removes every copy of the key with `fingerprint` from the account and
+/// all sites, after confirmation unless `force` is set.
+pub fn remove_fingerprint(
+    client: &ApiClient,
+    fingerprint: &str,
+    force: bool,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let Audit { groups, .. } = gather(client)?;
+
+    let Some((fingerprint, group)) = groups
+        .iter()
+        .find(|(fp, _)| normalize_fingerprint(fp) == normalize_fingerprint(fingerprint))
+    else {
+        return Err(ApiError::NotFound(format!(
+            "No SSH key with fingerprint {} was found",
+            fingerprint
+        )));
+    };
+
+    let action = format!("remove SSH key {} everywhere", fingerprint);
+    let details = || {
+        Ok(group
+            .installs
+            .iter()
+            .map(|install| {
+                (
+                    "Installed",
+                    format!(
+                        "{} (key {}, {})",
+                        install.location.label(),
+                        install.key_id,
+                        install.name
+                    ),
+                )
+            })
+            .collect())
+    };
+    if !force && !confirm::confirm(&action, details)? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
+    let results: Vec<Result<(), String>> = group
+        .installs
+        .iter()
+        .map(|install| {
+            let path = match &install.location {
+                Location::Account => format!("/api/v1/vector/ssh-keys/{}", install.key_id),
+                Location::Site(id) => {
+                    format!("/api/v1/vector/sites/{}/ssh-keys/{}", id, install.key_id)
+                }
+            };
+            client
+                .delete::<Value>(&path)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .collect();
+    let failures = results.iter().filter(|r| r.is_err()).count();
+
+    if format == OutputFormat::Json {
+        let data: Vec<Value> = group
+            .installs
+            .iter()
+            .zip(&results)
+            .map(|(install, result)| {
+                let mut entry = json!({
+                    "location": install.location.label(),
+                    "id": install.key_id,
+                    "removed": result.is_ok(),
+                });
+                if let Err(e) = result {
+                    entry["error"] = json!(e);
+                }
+                entry
+            })
+            .collect();
+        print_json(&json!({"fingerprint": fingerprint, "data": data}));
+    } else {
+        let rows: Vec<Vec<String>> = group
+            .installs
+            .iter()
+            .zip(&results)
+            .map(|(install, result)| {
+                vec![
+                    install.location.label(),
+                    install.key_id.clone(),
+                    install.name.clone(),
+                    match result {
+                        Ok(()) => format_status("success"),
+                        Err(e) => format!("{}: {}", format_status("failed"), e),
+                    },
+                ]
+            })
+            .collect();
+        print_table(vec!["Location", "Key ID", "Name", "Result"], rows);
+        print_message(&format!(
+            "Removed {} of {} copies of {}.",
+            results.len() - failures,
+            results.len(),
+            fingerprint
+        ));
+    }
+
+    if failures > 0 {
+        return Err(ApiError::Other(format!(
+            "{} of {} removals failed",
+            failures,
+            results.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_keys() {
+        let keys = vec![
+            installed(
+                Location::Account,
+                &json!({"id": "a1", "name": "jane", "fingerprint": "SHA256:abc"}),
+            ),
+            installed(
+                Location::Site("s1".into()),
+                &json!({"id": "k1", "name": "jane laptop", "fingerprint": "SHA256:abc"}),
+            ),
+            installed(
+                Location::Site("s2".into()),
+                &json!({"id": "k2", "name": "bob", "fingerprint": "SHA256:def"}),
+            ),
+        ];
+
+        let groups = group_keys(keys);
+        assert_eq!(groups.len(), 2);
+        let jane = &groups["SHA256:abc"];
+        assert!(jane.on_account());
+        assert_eq!(jane.sites(), ["s1"]);
+        assert_eq!(jane.names(), ["jane", "jane laptop"]);
+        assert!(!groups["SHA256:def"].on_account());
+    }
+
+    #[test]
+    fn test_coverage() {
+        assert_eq!(coverage(0, 3), "none");
+        assert_eq!(coverage(2, 3), "partial");
+        assert_eq!(coverage(3, 3), "complete");
+        assert_eq!(coverage(0, 0), "none");
+    }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        assert_eq!(normalize_fingerprint("SHA256:abc"), "abc");
+        assert_eq!(normalize_fingerprint("abc"), "abc");
+    }
+}
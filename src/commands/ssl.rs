@@ -3,7 +3,8 @@ use serde_json::Value;
 
 use crate::api::{ApiClient, ApiError};
 use crate::output::{
-    OutputFormat, format_bool, format_option, print_json, print_key_value, print_message,
+    OutputFormat, format_bool, format_option, format_status, print_json, print_key_value,
+    print_message,
 };
 
 #[derive(Debug, Serialize)]
@@ -23,7 +24,10 @@ pub fn status(client: &ApiClient, env_id: &str, format: OutputFormat) -> Result<
     let env = &response["data"];
 
     print_key_value(vec![
-        ("Status", env["status"].as_str().unwrap_or("-").to_string()),
+        (
+            "Status",
+            format_status(env["status"].as_str().unwrap_or("-")),
+        ),
         (
             "Provisioning Step",
             format_option(&env["provisioning_step"].as_str().map(String::from)),
@@ -0,0 +1,250 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+use crate::api::{ApiClient, ApiError};
+use crate::confirm;
+use crate::output::{OutputFormat, print_json, print_message, print_table};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagOp {
+    Add,
+    Remove,
+    Set,
+}
+
+/// A `tag add|remove|set` command.
+#[derive(Debug)]
+pub struct TagRequest {
+    pub op: TagOp,
+    /// Comma-separated IDs; `None` when applying to `matching_tag`.
+    pub ids: Option<String>,
+    pub tags: Vec<String>,
+    /// Apply to every resource that already has this tag.
+    pub matching_tag: Option<String>,
+    /// Set an empty tag list. Required for that, so that a forgotten
+    /// argument never wipes tags by accident.
+    pub clear: bool,
+}
+
+impl TagRequest {
+    /// Checks the tags before anything is fetched.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        match (self.op, self.tags.is_empty()) {
+            (TagOp::Set, true) if !self.clear => Err(ApiError::ValidationError(
+                "No tags given. Pass --clear to remove all tags".to_string(),
+            )),
+            (TagOp::Add | TagOp::Remove, true) => {
+                Err(ApiError::ValidationError("No tags given".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The IDs given on the command line.
+    pub fn ids(&self) -> Vec<String> {
+        self.ids.as_deref().map(split_ids).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct UpdateTagsRequest<'a> {
+    tags: &'a [String],
+}
+
+/// Computes the new tag list, keeping the existing order and dropping
+/// duplicates.
+fn apply(current: &[String], op: TagOp, tags: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = match op {
+        TagOp::Add => current.iter().chain(tags).cloned().collect(),
+        TagOp::Remove => current
+            .iter()
+            .filter(|t| !tags.contains(t))
+            .cloned()
+            .collect(),
+        TagOp::Set => tags.to_vec(),
+    };
+    let mut seen = HashSet::new();
+    result.retain(|t| seen.insert(t.clone()));
+    result
+}
+
+/// Splits a comma-separated list of IDs.
+pub fn split_ids(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn current_tags(resource: &Value) -> Vec<String> {
+    resource["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str())
+        .map(String::from)
+        .collect()
+}
+
+/// IDs of the resources that have `tag`.
+pub fn tagged(resources: &[Value], tag: &str) -> Vec<String> {
+    resources
+        .iter()
+        .filter(|r| current_tags(r).iter().any(|t| t == tag))
+        .filter_map(|r| r["id"].as_str().map(String::from))
+        .collect()
+}
+
+/// Reads, updates and writes back the tags of each resource under
+/// `base_path` (e.g. `/api/v1/vector/sites`). With several IDs, failures are
+/// reported per resource and the command fails at the end. Clearing tags
+/// asks for confirmation first.
+pub fn update(
+    client: &ApiClient,
+    base_path: &str,
+    ids: &[String],
+    request: &TagRequest,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    if ids.is_empty() {
+        return Err(ApiError::ValidationError(
+            "No matching resources to tag".to_string(),
+        ));
+    }
+    let targets = match ids {
+        [id] => id.clone(),
+        _ => format!("{} resources", ids.len()),
+    };
+    if request.clear
+        && !confirm::confirm(&format!("remove all tags from {}", targets), || {
+            Ok(vec![("IDs", ids.join(", "))])
+        })?
+    {
+        print_message("Aborted.");
+        return Ok(());
+    }
+    let (op, tags) = (request.op, &request.tags);
+
+    let mut results: Vec<Value> = Vec::new();
+    let mut failures = 0;
+
+    for id in ids {
+        let path = format!("{}/{}", base_path, id);
+        let outcome = client.get::<Value>(&path).and_then(|response| {
+            let current = current_tags(&response["data"]);
+            let updated = apply(&current, op, tags);
+            if updated != current {
+                client.put::<Value, _>(&path, &UpdateTagsRequest { tags: &updated })?;
+            }
+            Ok((updated != current, updated))
+        });
+
+        match outcome {
+            Ok((changed, updated)) => {
+                results.push(json!({"id": id, "tags": updated, "changed": changed}))
+            }
+            Err(e) if ids.len() == 1 => return Err(e),
+            Err(e) => {
+                failures += 1;
+                results.push(json!({"id": id, "error": e.to_string()}));
+            }
+        }
+    }
+
+    if format == OutputFormat::Json {
+        print_json(&json!({ "data": results }));
+    } else {
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|r| {
+                let tags: Vec<&str> = r["tags"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|t| t.as_str())
+                    .collect();
+                let result = match (r["error"].as_str(), r["changed"].as_bool()) {
+                    (Some(error), _) => format!("Failed: {}", error),
+                    (None, Some(true)) => "Updated".to_string(),
+                    _ => "Unchanged".to_string(),
+                };
+                vec![
+                    r["id"].as_str().unwrap_or("-").to_string(),
+                    if tags.is_empty() {
+                        "-".to_string()
+                    } else {
+                        tags.join(", ")
+                    },
+                    result,
+                ]
+            })
+            .collect();
+        print_table(vec!["ID", "Tags", "Result"], rows);
+    }
+
+    if failures > 0 {
+        return Err(ApiError::Other(format!(
+            "{} of {} tag updates failed",
+            failures,
+            ids.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_apply_tags() {
+        let current = strings(&["prod", "wp"]);
+
+        assert_eq!(
+            apply(&current, TagOp::Add, &strings(&["wp", "legacy"])),
+            ["prod", "wp", "legacy"]
+        );
+        assert_eq!(apply(&current, TagOp::Remove, &strings(&["prod"])), ["wp"]);
+        assert_eq!(
+            apply(&current, TagOp::Set, &strings(&["a", "a", "b"])),
+            ["a", "b"]
+        );
+        assert!(apply(&current, TagOp::Set, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_set_without_tags_requires_clear() {
+        let mut request = TagRequest {
+            op: TagOp::Set,
+            ids: Some("s1,s2".to_string()),
+            tags: Vec::new(),
+            matching_tag: None,
+            clear: false,
+        };
+        assert!(request.validate().is_err());
+        request.clear = true;
+        assert!(request.validate().is_ok());
+        assert_eq!(request.ids(), ["s1", "s2"]);
+
+        request.op = TagOp::Add;
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_tagged() {
+        let resources = vec![
+            json!({"id": "e1", "tags": ["legacy", "wp"]}),
+            json!({"id": "e2", "tags": ["wp"]}),
+            json!({"id": "e3"}),
+        ];
+        assert_eq!(tagged(&resources, "legacy"), ["e1"]);
+        assert_eq!(tagged(&resources, "wp"), ["e1", "e2"]);
+    }
+}
@@ -1,10 +1,15 @@
+use jiff::Timestamp;
 use serde::Serialize;
 use serde_json::Value;
 
 use crate::api::{ApiClient, ApiError};
+use crate::commands::site;
+use crate::confirm;
+use crate::diff::{self, Field};
 use crate::output::{
     OutputFormat, format_option, print_json, print_key_value, print_message, print_table,
 };
+use crate::update::{self, Updated};
 
 #[derive(Debug, Serialize)]
 struct CreateRateLimitRequest {
@@ -218,6 +223,19 @@ pub fn rate_limit_create(
     Ok(())
 }
 
+/// Fields `waf rate-limit update` can change.
+const RATE_LIMIT_FIELDS: &[Field] = &[
+    ("Name", "name"),
+    ("Description", "description"),
+    ("Request Count", "configuration.request_count"),
+    ("Timeframe (s)", "configuration.timeframe"),
+    ("Block Time (s)", "configuration.block_time"),
+    ("Value", "configuration.value"),
+    ("Operator", "configuration.operator"),
+    ("Variables", "configuration.variables"),
+    ("Transformations", "configuration.transformations"),
+];
+
 #[allow(clippy::too_many_arguments)]
 pub fn rate_limit_update(
     client: &ApiClient,
@@ -232,6 +250,7 @@ pub fn rate_limit_update(
     operator: Option<String>,
     variables: Option<Vec<String>>,
     transformations: Option<Vec<String>>,
+    unmodified_since: Option<Timestamp>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = UpdateRateLimitRequest {
@@ -246,12 +265,16 @@ pub fn rate_limit_update(
         transformations,
     };
 
-    let response: Value = client.put(
+    let Updated { before, response } = update::put_unmodified(
+        client,
+        &format!("Rate limit {}", rule_id),
         &format!(
             "/api/v1/vector/sites/{}/waf/rate-limits/{}",
             site_id, rule_id
         ),
         &body,
+        unmodified_since,
+        RATE_LIMIT_FIELDS,
     )?;
 
     if format == OutputFormat::Json {
@@ -260,6 +283,13 @@ pub fn rate_limit_update(
     }
 
     print_message("Rate limit updated successfully.");
+    diff::print_changes(diff::changes(
+        &before["data"],
+        &response["data"],
+        &body,
+        RATE_LIMIT_FIELDS,
+        &[],
+    ));
     Ok(())
 }
 
@@ -269,6 +299,27 @@ pub fn rate_limit_delete(
     rule_id: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(
+        &format!("delete rate limit {} on site {}", rule_id, site_id),
+        || {
+            confirm::fetch_details(
+                client,
+                &format!(
+                    "/api/v1/vector/sites/{}/waf/rate-limits/{}",
+                    site_id, rule_id
+                ),
+                &[
+                    ("ID", "id"),
+                    ("Name", "name"),
+                    ("Description", "description"),
+                ],
+            )
+        },
+    )? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!(
         "/api/v1/vector/sites/{}/waf/rate-limits/{}",
         site_id, rule_id
@@ -350,6 +401,18 @@ pub fn blocked_ip_remove(
     ip: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(
+        &format!("remove IP {} from the blocklist of site {}", ip, site_id),
+        || {
+            let mut details = site::site_details(client, site_id)?;
+            details.push(("IP", ip.to_string()));
+            Ok(details)
+        },
+    )? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!(
         "/api/v1/vector/sites/{}/waf/blocked-ips/{}",
         site_id, ip
@@ -430,6 +493,21 @@ pub fn blocked_referrer_remove(
     hostname: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(
+        &format!(
+            "remove referrer {} from the blocklist of site {}",
+            hostname, site_id
+        ),
+        || {
+            let mut details = site::site_details(client, site_id)?;
+            details.push(("Referrer", hostname.to_string()));
+            Ok(details)
+        },
+    )? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!(
         "/api/v1/vector/sites/{}/waf/blocked-referrers/{}",
         site_id, hostname
@@ -510,6 +588,21 @@ pub fn allowed_referrer_remove(
     hostname: &str,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
+    if !confirm::confirm(
+        &format!(
+            "remove referrer {} from the allowlist of site {}",
+            hostname, site_id
+        ),
+        || {
+            let mut details = site::site_details(client, site_id)?;
+            details.push(("Referrer", hostname.to_string()));
+            Ok(details)
+        },
+    )? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!(
         "/api/v1/vector/sites/{}/waf/allowed-referrers/{}",
         site_id, hostname
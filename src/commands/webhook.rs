@@ -1,11 +1,15 @@
+use jiff::Timestamp;
 use serde::Serialize;
 use serde_json::Value;
 
 use crate::api::{ApiClient, ApiError};
+use crate::confirm;
+use crate::diff::{self, Field};
 use crate::output::{
-    OutputFormat, extract_pagination, format_option, print_json, print_key_value, print_message,
+    OutputFormat, extract_pagination, format_timestamp, print_json, print_key_value, print_message,
     print_pagination, print_table,
 };
+use crate::update::{self, Updated};
 
 #[derive(Debug, Serialize)]
 struct PaginationQuery {
@@ -105,11 +109,11 @@ pub fn show(client: &ApiClient, webhook_id: &str, format: OutputFormat) -> Resul
         ),
         (
             "Created",
-            format_option(&webhook["created_at"].as_str().map(String::from)),
+            format_timestamp(&webhook["created_at"].as_str().map(String::from)),
         ),
         (
             "Updated",
-            format_option(&webhook["updated_at"].as_str().map(String::from)),
+            format_timestamp(&webhook["updated_at"].as_str().map(String::from)),
         ),
     ]);
 
@@ -148,6 +152,16 @@ pub fn create(
     Ok(())
 }
 
+/// Fields `webhook update` can change; the signing secret is only reported
+/// as changed or unchanged.
+const UPDATE_FIELDS: &[Field] = &[
+    ("Name", "name"),
+    ("URL", "url"),
+    ("Events", "events"),
+    ("Enabled", "enabled"),
+];
+const UPDATE_SECRETS: &[Field] = &[("Secret", "secret")];
+
 #[allow(clippy::too_many_arguments)]
 pub fn update(
     client: &ApiClient,
@@ -157,6 +171,7 @@ pub fn update(
     events: Option<Vec<String>>,
     secret: Option<String>,
     enabled: Option<bool>,
+    unmodified_since: Option<Timestamp>,
     format: OutputFormat,
 ) -> Result<(), ApiError> {
     let body = UpdateWebhookRequest {
@@ -167,7 +182,14 @@ pub fn update(
         enabled,
     };
 
-    let response: Value = client.put(&format!("/api/v1/vector/webhooks/{}", webhook_id), &body)?;
+    let Updated { before, response } = update::put_unmodified(
+        client,
+        &format!("Webhook {}", webhook_id),
+        &format!("/api/v1/vector/webhooks/{}", webhook_id),
+        &body,
+        unmodified_since,
+        UPDATE_FIELDS,
+    )?;
 
     if format == OutputFormat::Json {
         print_json(&response);
@@ -175,10 +197,33 @@ pub fn update(
     }
 
     print_message("Webhook updated successfully.");
+    diff::print_changes(diff::changes(
+        &before["data"],
+        &response["data"],
+        &body,
+        UPDATE_FIELDS,
+        UPDATE_SECRETS,
+    ));
     Ok(())
 }
 
 pub fn delete(client: &ApiClient, webhook_id: &str, format: OutputFormat) -> Result<(), ApiError> {
+    if !confirm::confirm(&format!("delete webhook {}", webhook_id), || {
+        confirm::fetch_details(
+            client,
+            &format!("/api/v1/vector/webhooks/{}", webhook_id),
+            &[
+                ("ID", "id"),
+                ("Name", "name"),
+                ("URL", "url"),
+                ("Events", "events"),
+            ],
+        )
+    })? {
+        print_message("Aborted.");
+        return Ok(());
+    }
+
     let response: Value = client.delete(&format!("/api/v1/vector/webhooks/{}", webhook_id))?;
 
     if format == OutputFormat::Json {
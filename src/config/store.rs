@@ -10,6 +10,11 @@ use super::paths::{config_dir, config_file, credentials_file};
 pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pager: Option<bool>,
+    /// Whether production environments need extra confirmation (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub production_guard: Option<bool>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
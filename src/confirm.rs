@@ -0,0 +1,169 @@
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::Value;
+
+use crate::api::{ApiClient, ApiError};
+
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+static PRODUCTION_GUARD: AtomicBool = AtomicBool::new(true);
+
+/// Answers every confirmation with yes, as with `--yes`.
+pub fn init_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+/// Turns the production guard on or off (`production_guard` in config.json).
+pub fn init_production_guard(enabled: bool) {
+    PRODUCTION_GUARD.store(enabled, Ordering::Relaxed);
+}
+
+/// Outcome of [`guard_production`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guard {
+    /// Not production, the guard is off, or `--confirm-production` was given.
+    Proceed,
+    /// The user typed the environment name.
+    Confirmed,
+    /// The user typed something else.
+    Declined,
+}
+
+/// Asks before a destructive `action` such as "delete environment e1".
+/// The prompt and the details of what is affected go to stderr, so they
+/// stay visible when stdout is piped or paged. `details` is only called
+/// when a prompt is actually shown.
+///
+/// Returns whether to go ahead. Without `--yes`, fails instead of prompting
+/// when stdin is not a terminal, so scripts never hang or act by accident.
+pub fn confirm<F>(action: &str, details: F) -> Result<bool, ApiError>
+where
+    F: FnOnce() -> Result<Vec<(&'static str, String)>, ApiError>,
+{
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    ensure_confirmable(action)?;
+
+    let details = details()?;
+    let width = details.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in &details {
+        eprintln!("  {:width$}  {}", key, value, width = width);
+    }
+    eprint!("Are you sure you want to {}? [y/N] ", action);
+    let _ = io::stderr().flush();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).ok();
+    Ok(matches!(
+        input.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Fails unless [`confirm`] can get an answer for `action`: `--yes` was
+/// given or stdin is a terminal. Lets commands fail before sending any
+/// request.
+pub fn ensure_confirmable(action: &str) -> Result<(), ApiError> {
+    if ASSUME_YES.load(Ordering::Relaxed) || atty::is(atty::Stream::Stdin) {
+        return Ok(());
+    }
+    Err(ApiError::ValidationError(format!(
+        "Refusing to {} without confirmation: stdin is not a terminal. Pass --yes to proceed.",
+        action
+    )))
+}
+
+/// Fetches `path` and picks `(label, field)` pairs from its data for
+/// [`confirm`] to show.
+pub fn fetch_details(
+    client: &ApiClient,
+    path: &str,
+    fields: &[(&'static str, &str)],
+) -> Result<Vec<(&'static str, String)>, ApiError> {
+    let response: Value = client.get(path)?;
+    Ok(details(&response["data"], fields))
+}
+
+/// Picks `(label, field)` pairs from a resource for [`confirm`] to show.
+pub fn details(data: &Value, fields: &[(&'static str, &str)]) -> Vec<(&'static str, String)> {
+    fields
+        .iter()
+        .map(|(label, field)| {
+            let value = match &data[*field] {
+                Value::String(s) => s.clone(),
+                Value::Bool(true) => "Yes".to_string(),
+                Value::Bool(false) => "No".to_string(),
+                Value::Null => "-".to_string(),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|i| {
+                        i.as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| i.to_string())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                other => other.to_string(),
+            };
+            (*label, value)
+        })
+        .collect()
+}
+
+/// Looks up environment `env_id` and, when it is production, requires the
+/// user to type its name before `action` (e.g. "delete the environment")
+/// unless `confirmed` (`--confirm-production`) is set. `--yes` does not
+/// skip this.
+pub fn guard_production(
+    client: &ApiClient,
+    env_id: &str,
+    action: &str,
+    confirmed: bool,
+) -> Result<Guard, ApiError> {
+    guard_production_env(client, env_id, action, confirmed).map(|(guard, _)| guard)
+}
+
+/// Like [`guard_production`], also returning the environment's data when
+/// it had to be fetched, so callers need not fetch it again.
+pub fn guard_production_env(
+    client: &ApiClient,
+    env_id: &str,
+    action: &str,
+    confirmed: bool,
+) -> Result<(Guard, Option<Value>), ApiError> {
+    if confirmed || !PRODUCTION_GUARD.load(Ordering::Relaxed) {
+        return Ok((Guard::Proceed, None));
+    }
+
+    let mut response: Value = client.get(&format!("/api/v1/vector/environments/{}", env_id))?;
+    let env = response["data"].take();
+    if !env["is_production"].as_bool().unwrap_or(false) {
+        return Ok((Guard::Proceed, Some(env)));
+    }
+    let name = env["name"].as_str().unwrap_or(env_id);
+
+    if !atty::is(atty::Stream::Stdin) {
+        return Err(ApiError::ValidationError(format!(
+            "Environment {} ({}) is production; pass --confirm-production to {}.",
+            name, env_id, action
+        )));
+    }
+
+    eprintln!(
+        "Environment {} ({}) is production. This will {}.",
+        name, env_id, action
+    );
+    eprint!("Type the environment name to continue: ");
+    let _ = io::stderr().flush();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).ok();
+    let guard = if input.trim() == name {
+        Guard::Confirmed
+    } else {
+        eprintln!("The name did not match.");
+        Guard::Declined
+    };
+    Ok((guard, Some(env)))
+}
@@ -0,0 +1,144 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::output::{print_message, print_table};
+
+/// A resource field to compare: its label and its path in the resource,
+/// with nested fields separated by dots (e.g. `configuration.timeframe`).
+/// The request body sets it under the last path segment.
+pub type Field = (&'static str, &'static str);
+
+/// One row of the change table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub label: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> &'a Value {
+    path.split('.').fold(value, |v, key| &v[key])
+}
+
+fn body_key(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or(path)
+}
+
+fn render(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Bool(true) => "Yes".to_string(),
+        Value::Bool(false) => "No".to_string(),
+        Value::Array(items) if items.is_empty() => "-".to_string(),
+        Value::Array(items) => items.iter().map(render).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Compares `before` with the resource the update returned. When the
+/// response has no data (as in a dry run), the request body is applied to
+/// `before` instead, which previews the change. Only changed fields are
+/// listed; `secrets` are body fields shown only as changed or unchanged.
+pub fn changes<B: Serialize>(
+    before: &Value,
+    returned: &Value,
+    body: &B,
+    fields: &[Field],
+    secrets: &[Field],
+) -> Vec<Change> {
+    let body = serde_json::to_value(body).unwrap_or(Value::Null);
+    let has_data = returned.as_object().is_some_and(|data| !data.is_empty());
+
+    let mut rows: Vec<Change> = fields
+        .iter()
+        .filter_map(|(label, path)| {
+            let old = lookup(before, path);
+            let new = if has_data {
+                lookup(returned, path)
+            } else {
+                body.get(body_key(path)).unwrap_or(old)
+            };
+            // Compare rendered values so that e.g. null and [] both count as empty.
+            let (before, after) = (render(old), render(new));
+            (before != after).then_some(Change {
+                label,
+                before,
+                after,
+            })
+        })
+        .collect();
+
+    rows.extend(secrets.iter().filter_map(|(label, path)| {
+        let sent = body.get(body_key(path))?;
+        // Secret values are rarely returned; without one, sending a value
+        // counts as a change.
+        let unchanged = lookup(before, path) == sent;
+        Some(Change {
+            label,
+            before: "********".to_string(),
+            after: if unchanged { "unchanged" } else { "changed" }.to_string(),
+        })
+    }));
+    rows
+}
+
+/// Prints the change table, or a note when nothing changed.
+pub fn print_changes(changes: Vec<Change>) {
+    if changes.is_empty() {
+        print_message("No fields changed.");
+        return;
+    }
+    let rows = changes
+        .into_iter()
+        .map(|c| vec![c.label.to_string(), c.before, c.after])
+        .collect();
+    print_table(vec!["Field", "Before", "After"], rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIELDS: &[Field] = &[
+        ("Name", "name"),
+        ("Timeframe", "configuration.timeframe"),
+        ("Tags", "tags"),
+    ];
+
+    #[test]
+    fn test_changes_from_response() {
+        let before = json!({"name": "a", "configuration": {"timeframe": 60}, "tags": []});
+        let after = json!({"name": "b", "configuration": {"timeframe": 60}, "tags": ["x", "y"]});
+        assert_eq!(
+            changes(&before, &after, &json!({}), FIELDS, &[]),
+            vec![
+                Change {
+                    label: "Name",
+                    before: "a".into(),
+                    after: "b".into()
+                },
+                Change {
+                    label: "Tags",
+                    before: "-".into(),
+                    after: "x, y".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changes_preview_from_body() {
+        let before = json!({"name": "a", "configuration": {"timeframe": 60}, "tags": []});
+        let body = json!({"timeframe": 120, "value": "s3cret"});
+        let rows = changes(&before, &json!({}), &body, FIELDS, &[("Value", "value")]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            (rows[0].before.as_str(), rows[0].after.as_str()),
+            ("60", "120")
+        );
+        assert_eq!(rows[1].after, "changed");
+        assert!(!rows.iter().any(|c| c.after.contains("s3cret")));
+    }
+}
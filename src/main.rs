@@ -2,30 +2,106 @@ mod api;
 mod cli;
 mod commands;
 mod config;
+mod confirm;
+mod diff;
 mod output;
+mod pager;
+mod sshkey;
+mod timespec;
+mod update;
 
 use clap::Parser;
+use jiff::Timestamp;
 use serde_json::Value;
 use std::process;
+use std::time::Duration;
 
-use api::{ApiClient, ApiError, EXIT_SUCCESS};
+use api::{ApiClient, ApiError, EXIT_SUCCESS, init_dry_run};
 use cli::{
     AccountApiKeyCommands, AccountCommands, AccountSecretCommands, AccountSshKeyCommands,
-    AuthCommands, Cli, Commands, DbCommands, DbExportCommands, DbImportSessionCommands,
-    DeployCommands, EnvCommands, EnvDbCommands, EnvDbImportSessionCommands, EnvSecretCommands,
-    EventCommands, McpCommands, SiteCommands, SiteSshKeyCommands, SslCommands,
-    WafAllowedReferrerCommands, WafBlockedIpCommands, WafBlockedReferrerCommands, WafCommands,
-    WafRateLimitCommands, WebhookCommands,
+    AuthCommands, Cli, Commands, DbCommands, DbCredentialsArgs, DbExportCommands,
+    DbImportSessionCommands, DeployCommands, EnvCommands, EnvDbCommands,
+    EnvDbImportSessionCommands, EnvSecretCommands, EventCommands, LogFilterArgs, McpCommands,
+    PublicKeyArgs, SiteCommands, SiteLogsCommands, SiteSelectArgs, SiteSshKeyCommands,
+    SshKeyCommands, SslCommands, TagArgs, TagCommands, WafAllowedReferrerCommands,
+    WafBlockedIpCommands, WafBlockedReferrerCommands, WafCommands, WafRateLimitCommands, WaitArgs,
+    WebhookCommands,
+};
+use commands::credentials::Owner;
+use commands::logs::{ExportOptions, LogDisplay, LogsQuery, SearchOptions};
+use commands::purge::{Throttle, UrlSource};
+use commands::site::{Selection, SiteAction, Wait};
+use commands::ssh::Tool;
+use commands::tags::{TagOp, TagRequest};
+use commands::{
+    account, auth, blueprint, credentials, db, deploy, env, event, logs, mcp, purge, site, ssh,
+    ssh_key, ssl, waf, webhook,
 };
-use commands::{account, auth, db, deploy, env, event, mcp, site, ssl, waf, webhook};
 use config::{Config, Credentials};
-use output::{OutputFormat, print_error, print_json, print_message, print_table};
+use output::{
+    ColorChoice, OutputFormat, init_color, init_quiet, init_time_display, print_error, print_json,
+    print_message, print_table, start_capture, write_capture_to_file,
+};
+use sshkey::KeySource;
 
 fn main() {
     let cli = Cli::parse();
-    let format = OutputFormat::detect(cli.json, cli.no_json);
+    // Output written to a file is treated like piped output: JSON and no colors
+    // unless requested explicitly.
+    let to_file = cli.output_file.is_some();
+    let format = if cli.quiet || (to_file && !cli.no_json) {
+        OutputFormat::Json
+    } else {
+        OutputFormat::detect(cli.json, cli.no_json)
+    };
+    let color = if to_file && cli.color == ColorChoice::Auto {
+        ColorChoice::Never
+    } else {
+        cli.color
+    };
+    init_color(color);
+    init_quiet(cli.quiet);
+    // A broken config file is reported by commands that need it.
+    let config = Config::load().unwrap_or_default();
+    // Nothing changes in a dry run, so there is nothing to confirm.
+    init_dry_run(cli.dry_run);
+    confirm::init_assume_yes(cli.yes || cli.dry_run);
+    confirm::init_production_guard(config.production_guard.unwrap_or(true) && !cli.dry_run);
 
-    let result = run(cli.command, format);
+    if to_file {
+        start_capture();
+    } else {
+        pager::setup(cli.no_pager || !pages_output(&cli.command), config.pager);
+    }
+
+    let result = init_time_display(cli.absolute, cli.tz.as_deref())
+        .and_then(|()| {
+            if to_file && !captures_output(&cli.command) {
+                return Err(ApiError::ValidationError(
+                    "--output-file cannot be used with site logs --follow, site ssh or site sftp"
+                        .to_string(),
+                ));
+            }
+            Ok(())
+        })
+        .and_then(|()| run(cli.command, format));
+
+    let result = match cli.output_file {
+        Some(path) => result.and_then(|()| write_capture_to_file(&path)),
+        None => {
+            pager::finish();
+            result
+        }
+    };
+
+    let recorded = api::recorded_requests();
+    if recorded > 0 {
+        eprintln!(
+            "Dry run: would send the {} request{} above; nothing was changed.",
+            recorded,
+            if recorded == 1 { "" } else { "s" }
+        );
+    }
 
     match result {
         Ok(()) => process::exit(EXIT_SUCCESS),
@@ -36,6 +112,36 @@ fn main() {
     }
 }
 
+/// Whether a command's output can be captured for `--output-file`. Streams
+/// never finish and ssh/sftp hand the terminal over and exit directly.
+fn captures_output(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Site {
+            command: SiteCommands::Logs { follow: true, .. }
+                | SiteCommands::Ssh { .. }
+                | SiteCommands::Sftp { .. },
+        }
+    )
+}
+
+/// Whether a command's output is bounded, so it can be held back and paged.
+/// Streaming, waiting and interactive commands must write as they go.
+fn pages_output(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Deploy {
+            command: DeployCommands::Show { .. },
+        } | Commands::Site {
+            command: SiteCommands::Logs {
+                command: None,
+                follow: false,
+                ..
+            },
+        }
+    )
+}
+
 fn run(command: Commands, format: OutputFormat) -> Result<(), ApiError> {
     match command {
         Commands::Auth { command } => run_auth(command, format),
@@ -47,6 +153,7 @@ fn run(command: Commands, format: OutputFormat) -> Result<(), ApiError> {
         Commands::Waf { command } => run_waf(command, format),
         Commands::Account { command } => run_account(command, format),
         Commands::Event { command } => run_event(command, format),
+        Commands::SshKey { command } => run_ssh_key(command, format),
         Commands::Webhook { command } => run_webhook(command, format),
         Commands::PhpVersions => run_php_versions(format),
         Commands::Mcp { command } => run_mcp(command, format),
@@ -80,56 +187,372 @@ fn run_site(command: SiteCommands, format: OutputFormat) -> Result<(), ApiError>
     match command {
         SiteCommands::List { page, per_page } => site::list(&client, page, per_page, format),
         SiteCommands::Show { id } => site::show(&client, &id, format),
+        SiteCommands::Overview { id } => site::overview(&client, &id, format),
+        SiteCommands::Create {
+            from: Some(path),
+            keep_partial,
+            wait,
+            ..
+        } => blueprint::create(&client, &path, wait_options(wait), keep_partial, format),
         SiteCommands::Create {
             customer_id,
             dev_php_version,
             tags,
-        } => site::create(&client, &customer_id, &dev_php_version, tags, format),
+            wait,
+            ..
+        } => site::create(
+            &client,
+            &customer_id.unwrap_or_default(),
+            &dev_php_version.unwrap_or_default(),
+            tags,
+            wait_options(wait),
+            format,
+        ),
         SiteCommands::Update {
             id,
             customer_id,
             tags,
-        } => site::update(&client, &id, customer_id, tags, format),
+            if_unmodified_since,
+        } => site::update(
+            &client,
+            &id,
+            customer_id,
+            tags,
+            unmodified_since(if_unmodified_since)?,
+            format,
+        ),
         SiteCommands::Delete { id, force } => site::delete(&client, &id, force, format),
         SiteCommands::Clone {
             id,
             customer_id,
             dev_php_version,
             tags,
-        } => site::clone(&client, &id, customer_id, dev_php_version, tags, format),
-        SiteCommands::Suspend { id } => site::suspend(&client, &id, format),
-        SiteCommands::Unsuspend { id } => site::unsuspend(&client, &id, format),
+            wait,
+        } => site::clone(
+            &client,
+            &id,
+            customer_id,
+            dev_php_version,
+            tags,
+            wait_options(wait),
+            format,
+        ),
+        SiteCommands::Suspend { id, select, wait } => match id {
+            Some(id) if id != "-" => site::suspend(&client, &id, wait_options(wait), format),
+            id => run_site_bulk(
+                &client,
+                id,
+                select,
+                SiteAction::Suspend,
+                wait_options(wait),
+                format,
+            ),
+        },
+        SiteCommands::Unsuspend { id, select, wait } => match id {
+            Some(id) if id != "-" => site::unsuspend(&client, &id, wait_options(wait), format),
+            id => run_site_bulk(
+                &client,
+                id,
+                select,
+                SiteAction::Unsuspend,
+                wait_options(wait),
+                format,
+            ),
+        },
         SiteCommands::ResetSftpPassword { id } => site::reset_sftp_password(&client, &id, format),
         SiteCommands::ResetDbPassword { id } => site::reset_db_password(&client, &id, format),
-        SiteCommands::PurgeCache { id, cache_tag, url } => {
-            site::purge_cache(&client, &id, cache_tag, url, format)
+        SiteCommands::DbCredentials { id, output } => {
+            run_db_credentials(&client, Owner::Site, &id, output, format)
         }
+        SiteCommands::PurgeCache {
+            id,
+            select,
+            cache_tag,
+            url,
+            urls_from,
+            sitemap,
+            batch_size,
+            batch_delay,
+        } => match (
+            id,
+            urls_from
+                .map(UrlSource::List)
+                .or(sitemap.map(UrlSource::Sitemap)),
+        ) {
+            (Some(id), Some(source)) if id != "-" => {
+                let throttle = Throttle {
+                    batch_size: batch_size.into(),
+                    delay: Duration::from_millis(batch_delay),
+                };
+                purge::purge_urls(&client, &id, source, throttle, format)
+            }
+            (_, Some(_)) => Err(ApiError::ValidationError(
+                "--urls-from and --sitemap purge a single site; pass its ID".to_string(),
+            )),
+            (Some(id), None) if id != "-" => {
+                site::purge_cache(&client, &id, cache_tag, url, format)
+            }
+            (id, None) => run_site_bulk(
+                &client,
+                id,
+                select,
+                SiteAction::PurgeCache { cache_tag, url },
+                None,
+                format,
+            ),
+        },
+        SiteCommands::Logs {
+            command: Some(command),
+            ..
+        } => run_site_logs(&client, *command, format),
         SiteCommands::Logs {
+            command: None,
             id,
-            start_time,
-            end_time,
+            filter,
             limit,
-            environment,
-            deployment_id,
-            level,
             cursor,
-        } => site::logs(
+            follow,
+            interval,
+            fields,
+            raw,
+        } => logs::show(
+            &client,
+            // clap requires the ID when no subcommand is given.
+            &id.unwrap_or_default(),
+            logs_query(*filter, limit, cursor)?,
+            follow,
+            interval,
+            LogDisplay { fields, raw },
+            format,
+        ),
+        SiteCommands::WpReconfig { id, select, wait } => match id {
+            Some(id) if id != "-" => site::wp_reconfig(&client, &id, wait_options(wait), format),
+            id => run_site_bulk(
+                &client,
+                id,
+                select,
+                SiteAction::WpReconfig,
+                wait_options(wait),
+                format,
+            ),
+        },
+        SiteCommands::Wait {
+            id,
+            status,
+            timeout,
+            poll_interval,
+        } => site::wait(
             &client,
             &id,
-            start_time,
-            end_time,
+            &status,
+            Wait {
+                timeout: Duration::from_secs(timeout),
+                interval: Duration::from_secs(poll_interval),
+            },
+            format,
+        ),
+        SiteCommands::Ssh { id, args } => ssh::connect(&client, &id, Tool::Ssh, &args),
+        SiteCommands::Sftp { id } => ssh::connect(&client, &id, Tool::Sftp, &[]),
+        SiteCommands::SshConfig {
+            ids,
+            select,
+            file,
+            print,
+        } => {
+            let selection = ids.is_empty().then_some(Selection {
+                stdin: false,
+                tag: select.tag,
+                customer_id: select.customer_id,
+            });
+            ssh::config(
+                &client,
+                ids,
+                selection,
+                select.concurrency.into(),
+                file,
+                print,
+                format,
+            )
+        }
+        SiteCommands::SshKey { command } => run_site_ssh_key(&client, command, format),
+        SiteCommands::Tag { command } => site::tag(&client, tag_request(command), format),
+    }
+}
+
+fn tag_request(command: TagCommands) -> TagRequest {
+    let (op, args, clear) = match command {
+        TagCommands::Add { args } => (TagOp::Add, args, false),
+        TagCommands::Remove { args } => (TagOp::Remove, args, false),
+        TagCommands::Set { args, clear } => (TagOp::Set, args, clear),
+    };
+    let TagArgs {
+        id,
+        mut tags,
+        matching_tag,
+    } = args;
+    // With --matching-tag there is no ID, so the first value is a tag.
+    let ids = match matching_tag {
+        Some(_) => {
+            tags.splice(0..0, id);
+            None
+        }
+        None => id,
+    };
+    TagRequest {
+        op,
+        ids,
+        tags,
+        matching_tag,
+        clear,
+    }
+}
+
+/// Runs `action` on the sites chosen by the selection flags, or on IDs read
+/// from stdin when the ID is `-`.
+fn run_site_bulk(
+    client: &ApiClient,
+    id: Option<String>,
+    select: SiteSelectArgs,
+    action: SiteAction,
+    wait: Option<Wait>,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    let selection = Selection {
+        stdin: id.is_some(),
+        tag: select.tag,
+        customer_id: select.customer_id,
+    };
+    site::bulk(
+        client,
+        selection,
+        action,
+        wait,
+        select.concurrency.into(),
+        format,
+    )
+}
+
+/// Loads and validates the key, returning its name and OpenSSH text.
+fn public_key_input(args: PublicKeyArgs) -> Result<(String, String), ApiError> {
+    let source = match (args.public_key, args.public_key_file, args.from_agent) {
+        (Some(text), _, _) => KeySource::Inline(text),
+        (_, Some(path), _) => KeySource::File(path),
+        (_, _, Some(pattern)) => KeySource::Agent((!pattern.is_empty()).then_some(pattern)),
+        _ => KeySource::Default,
+    };
+    let key = sshkey::load(source)?;
+    let name = args.name.or(key.comment.clone()).ok_or_else(|| {
+        ApiError::ValidationError("The key has no comment; pass --name".to_string())
+    })?;
+    Ok((name, key.to_openssh()))
+}
+
+fn run_db_credentials(
+    client: &ApiClient,
+    owner: Owner,
+    id: &str,
+    args: DbCredentialsArgs,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    credentials::show(
+        client,
+        owner,
+        id,
+        args.format,
+        args.reset_password,
+        args.env_file.as_deref(),
+        format,
+    )
+}
+
+/// A dry run creates and changes nothing, so there is nothing to wait for.
+fn wait_options(args: WaitArgs) -> Option<Wait> {
+    if args.wait && api::is_dry_run() {
+        eprintln!("Dry run: not waiting.");
+        return None;
+    }
+    args.wait.then(|| Wait {
+        timeout: Duration::from_secs(args.timeout),
+        interval: Duration::from_secs(args.poll_interval),
+    })
+}
+
+fn unmodified_since(input: Option<String>) -> Result<Option<Timestamp>, ApiError> {
+    input
+        .map(|input| timespec::resolve_timestamp(&input, "--if-unmodified-since"))
+        .transpose()
+}
+
+fn run_site_logs(
+    client: &ApiClient,
+    command: SiteLogsCommands,
+    format: OutputFormat,
+) -> Result<(), ApiError> {
+    match command {
+        SiteLogsCommands::Export {
+            id,
+            filter,
+            output,
+            format: file_format,
+            gzip,
             limit,
-            environment,
-            deployment_id,
-            level,
             cursor,
+            resume,
+        } => logs::export(
+            client,
+            &id,
+            logs_query(filter, limit, cursor)?,
+            ExportOptions {
+                path: output,
+                format: file_format,
+                gzip,
+                resume,
+            },
+            format,
+        ),
+        SiteLogsCommands::Search {
+            id,
+            filter,
+            grep,
+            ignore_case,
+            count_by,
+            top,
+            fields,
+            max_pages,
+            limit,
+        } => logs::search(
+            client,
+            &id,
+            logs_query(filter, limit, None)?,
+            SearchOptions {
+                pattern: grep,
+                ignore_case,
+                count_by,
+                top,
+                fields,
+                max_pages,
+            },
             format,
         ),
-        SiteCommands::WpReconfig { id } => site::wp_reconfig(&client, &id, format),
-        SiteCommands::SshKey { command } => run_site_ssh_key(&client, command, format),
     }
 }
 
+fn logs_query(
+    filter: LogFilterArgs,
+    limit: Option<u32>,
+    cursor: Option<String>,
+) -> Result<LogsQuery, ApiError> {
+    Ok(LogsQuery {
+        start_time: timespec::resolve(filter.start_time, "--start-time")?,
+        end_time: timespec::resolve(filter.end_time, "--end-time")?,
+        limit,
+        environment: filter.environment,
+        deployment_id: filter.deployment_id,
+        level: filter.level,
+        cursor,
+    })
+}
+
 fn run_site_ssh_key(
     client: &ApiClient,
     command: SiteSshKeyCommands,
@@ -141,17 +564,27 @@ fn run_site_ssh_key(
             page,
             per_page,
         } => site::ssh_key_list(client, &site_id, page, per_page, format),
-        SiteSshKeyCommands::Add {
-            site_id,
-            name,
-            public_key,
-        } => site::ssh_key_add(client, &site_id, &name, &public_key, format),
+        SiteSshKeyCommands::Add { site_id, key } => {
+            let (name, public_key) = public_key_input(key)?;
+            site::ssh_key_add(client, &site_id, &name, &public_key, format)
+        }
         SiteSshKeyCommands::Remove { site_id, key_id } => {
             site::ssh_key_remove(client, &site_id, &key_id, format)
         }
     }
 }
 
+fn run_ssh_key(command: SshKeyCommands, format: OutputFormat) -> Result<(), ApiError> {
+    let client = get_client()?;
+    match command {
+        SshKeyCommands::Audit {
+            remove_fingerprint: Some(fingerprint),
+            force,
+        } => ssh_key::remove_fingerprint(&client, &fingerprint, force, format),
+        SshKeyCommands::Audit { .. } => ssh_key::audit(&client, format),
+    }
+}
+
 fn run_env(command: EnvCommands, format: OutputFormat) -> Result<(), ApiError> {
     let client = get_client()?;
 
@@ -184,11 +617,27 @@ fn run_env(command: EnvCommands, format: OutputFormat) -> Result<(), ApiError> {
             name,
             custom_domain,
             tags,
-        } => env::update(&client, &env_id, name, custom_domain, tags, format),
-        EnvCommands::Delete { env_id } => env::delete(&client, &env_id, format),
+            if_unmodified_since,
+        } => env::update(
+            &client,
+            &env_id,
+            name,
+            custom_domain,
+            tags,
+            unmodified_since(if_unmodified_since)?,
+            format,
+        ),
+        EnvCommands::Delete {
+            env_id,
+            confirm_production,
+        } => env::delete(&client, &env_id, confirm_production, format),
         EnvCommands::ResetDbPassword { env_id } => env::reset_db_password(&client, &env_id, format),
+        EnvCommands::DbCredentials { env_id, output } => {
+            run_db_credentials(&client, Owner::Environment, &env_id, output, format)
+        }
         EnvCommands::Secret { command } => run_env_secret(&client, command, format),
         EnvCommands::Db { command } => run_env_db(&client, command, format),
+        EnvCommands::Tag { command } => env::tag(&client, tag_request(command), format),
     }
 }
 
@@ -215,7 +664,16 @@ fn run_env_secret(
             key,
             value,
             no_secret,
-        } => env::secret_update(client, &secret_id, key, value, no_secret, format),
+            if_unmodified_since,
+        } => env::secret_update(
+            client,
+            &secret_id,
+            key,
+            value,
+            no_secret,
+            unmodified_since(if_unmodified_since)?,
+            format,
+        ),
         EnvSecretCommands::Delete { secret_id } => env::secret_delete(client, &secret_id, format),
     }
 }
@@ -233,6 +691,7 @@ fn run_env_db(
             disable_foreign_keys,
             search_replace_from,
             search_replace_to,
+            confirm_production,
         } => env::db_import(
             client,
             &env_id,
@@ -241,6 +700,7 @@ fn run_env_db(
             disable_foreign_keys,
             search_replace_from,
             search_replace_to,
+            confirm_production,
             format,
         ),
         EnvDbCommands::ImportSession { command } => {
@@ -250,7 +710,15 @@ fn run_env_db(
             env_id,
             drop_tables,
             disable_foreign_keys,
-        } => env::db_promote(client, &env_id, drop_tables, disable_foreign_keys, format),
+            confirm_production,
+        } => env::db_promote(
+            client,
+            &env_id,
+            drop_tables,
+            disable_foreign_keys,
+            confirm_production,
+            format,
+        ),
         EnvDbCommands::PromoteStatus { env_id, promote_id } => {
             env::db_promote_status(client, &env_id, &promote_id, format)
         }
@@ -305,11 +773,26 @@ fn run_deploy(command: DeployCommands, format: OutputFormat) -> Result<(), ApiEr
             env_id,
             include_uploads,
             include_database,
-        } => deploy::trigger(&client, &env_id, include_uploads, include_database, format),
+            confirm_production,
+        } => deploy::trigger(
+            &client,
+            &env_id,
+            include_uploads,
+            include_database,
+            confirm_production,
+            format,
+        ),
         DeployCommands::Rollback {
             env_id,
             target_deployment_id,
-        } => deploy::rollback(&client, &env_id, target_deployment_id, format),
+            confirm_production,
+        } => deploy::rollback(
+            &client,
+            &env_id,
+            target_deployment_id,
+            confirm_production,
+            format,
+        ),
     }
 }
 
@@ -460,6 +943,7 @@ fn run_waf_rate_limit(
             operator,
             variables,
             transformations,
+            if_unmodified_since,
         } => waf::rate_limit_update(
             client,
             &site_id,
@@ -473,6 +957,7 @@ fn run_waf_rate_limit(
             operator,
             variables,
             transformations,
+            unmodified_since(if_unmodified_since)?,
             format,
         ),
         WafRateLimitCommands::Delete { site_id, rule_id } => {
@@ -554,7 +1039,8 @@ fn run_account_ssh_key(
             account::ssh_key_list(client, page, per_page, format)
         }
         AccountSshKeyCommands::Show { key_id } => account::ssh_key_show(client, &key_id, format),
-        AccountSshKeyCommands::Create { name, public_key } => {
+        AccountSshKeyCommands::Create { key } => {
+            let (name, public_key) = public_key_input(key)?;
             account::ssh_key_create(client, &name, &public_key, format)
         }
         AccountSshKeyCommands::Delete { key_id } => {
@@ -622,7 +1108,15 @@ fn run_event(command: EventCommands, format: OutputFormat) -> Result<(), ApiErro
             event: event_type,
             page,
             per_page,
-        } => event::list(&client, from, to, event_type, page, per_page, format),
+        } => event::list(
+            &client,
+            timespec::resolve(from, "--from")?,
+            timespec::resolve(to, "--to")?,
+            event_type,
+            page,
+            per_page,
+            format,
+        ),
     }
 }
 
@@ -645,6 +1139,7 @@ fn run_webhook(command: WebhookCommands, format: OutputFormat) -> Result<(), Api
             events,
             secret,
             enabled,
+            if_unmodified_since,
         } => webhook::update(
             &client,
             &webhook_id,
@@ -653,6 +1148,7 @@ fn run_webhook(command: WebhookCommands, format: OutputFormat) -> Result<(), Api
             events,
             secret,
             enabled,
+            unmodified_since(if_unmodified_since)?,
             format,
         ),
         WebhookCommands::Delete { webhook_id } => webhook::delete(&client, &webhook_id, format),
@@ -1,6 +1,31 @@
+use clap::ValueEnum;
 use comfy_table::{ContentArrangement, Table};
+use jiff::Timestamp;
+use jiff::tz::TimeZone;
 use serde::Serialize;
 use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::api::{self, ApiError};
+
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+const RED: &str = "31";
+const DIM: &str = "2";
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static CAPTURE: Mutex<Option<String>> = Mutex::new(None);
+static TIME_DISPLAY: OnceLock<TimeDisplay> = OnceLock::new();
+
+struct TimeDisplay {
+    absolute: bool,
+    tz: TimeZone,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OutputFormat {
@@ -24,15 +49,248 @@ impl OutputFormat {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                let dumb_term = std::env::var("TERM").is_ok_and(|t| t == "dumb");
+                !no_color && !dumb_term && atty::is(atty::Stream::Stdout)
+            }
+        }
+    }
+}
+
+pub fn init_color(choice: ColorChoice) {
+    COLOR_ENABLED.store(choice.enabled(), Ordering::Relaxed);
+}
+
+/// Configures how timestamps are rendered in table output. Naming a `tz`
+/// implies absolute timestamps; without one, the system time zone (which
+/// honors `TZ`) is used.
+pub fn init_time_display(absolute: bool, tz: Option<&str>) -> Result<(), ApiError> {
+    let absolute = absolute || tz.is_some();
+    let tz = match tz {
+        Some(name) => TimeZone::get(name)
+            .map_err(|_| ApiError::ValidationError(format!("Unknown time zone: {}", name)))?,
+        None => TimeZone::system(),
+    };
+    let _ = TIME_DISPLAY.set(TimeDisplay { absolute, tz });
+    Ok(())
+}
+
+/// The time zone used for display, which is also how local times given on
+/// the command line are interpreted.
+pub fn display_time_zone() -> TimeZone {
+    match TIME_DISPLAY.get() {
+        Some(display) => display.tz.clone(),
+        None => TimeZone::system(),
+    }
+}
+
+fn paint(text: &str, code: &str) -> String {
+    if COLOR_ENABLED.load(Ordering::Relaxed) {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn status_color(status: &str) -> Option<&'static str> {
+    match status.to_ascii_lowercase().as_str() {
+        "active" | "success" | "succeeded" | "successful" | "completed" | "complete"
+        | "deployed" | "issued" | "ready" | "healthy" | "enabled" => Some(GREEN),
+        "pending" | "provisioning" | "queued" | "running" | "in_progress" | "processing"
+        | "deploying" | "cloning" | "importing" | "exporting" | "suspending" | "unsuspending"
+        | "uploading" | "partial" => Some(YELLOW),
+        "failed" | "failure" | "error" | "suspended" | "cancelled" | "canceled" | "expired"
+        | "disabled" => Some(RED),
+        _ => None,
+    }
+}
+
+fn level_color(level: &str) -> Option<&'static str> {
+    match level.to_ascii_lowercase().as_str() {
+        "emergency" | "alert" | "critical" | "crit" | "fatal" | "error" | "err" => Some(RED),
+        "warning" | "warn" | "notice" => Some(YELLOW),
+        "info" => Some(GREEN),
+        "debug" | "trace" => Some(DIM),
+        _ => None,
+    }
+}
+
+/// Colors a resource status by meaning: green for healthy, yellow for in
+/// progress, red for failed or suspended.
+pub fn format_status(status: &str) -> String {
+    match status_color(status) {
+        Some(code) => paint(status, code),
+        None => dim_placeholder(status),
+    }
+}
+
+/// Colors a log level using the same palette as statuses.
+pub fn format_level(level: &str) -> String {
+    match level_color(level) {
+        Some(code) => paint(level, code),
+        None => dim_placeholder(level),
+    }
+}
+
+pub fn is_log_level(value: &str) -> bool {
+    level_color(value).is_some()
+}
+
+fn dim_placeholder(value: &str) -> String {
+    if value == "-" {
+        paint(value, DIM)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Enables quiet mode: commands print only resource IDs and skip all
+/// decorative messages. Commands run in JSON mode while quiet, so
+/// `print_json` is the single place that reduces a response to its IDs.
+pub fn init_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Buffers everything written to stdout until `take_capture` is called, so
+/// the complete output can be handed to a pager.
+pub fn start_capture() {
+    *CAPTURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(String::new());
+}
+
+pub fn take_capture() -> Option<String> {
+    CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Writes the captured output to `path` via a temporary file and rename, so
+/// readers never observe a partially written file.
+pub fn write_capture_to_file(path: &Path) -> Result<(), ApiError> {
+    let output = take_capture().unwrap_or_default();
+    write_atomic(path, output.as_bytes(), false)
+}
+
+/// Replaces `path` with `contents` through a temporary file in the same
+/// directory, so readers never see a partial file. `private` files are
+/// created readable by the owner only (mode 0600 on Unix).
+pub fn write_atomic(path: &Path, contents: &[u8], private: bool) -> Result<(), ApiError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| ApiError::Other(format!("Invalid output file: {}", path.display())))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+
+    let result = options
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        ApiError::Other(format!("Failed to write {}: {}", path.display(), e))
+    })
+}
+
+fn emit(line: &str) {
+    emit_into(&mut CAPTURE.lock().unwrap_or_else(|e| e.into_inner()), line);
+}
+
+/// Appends `line` to `capture` when capturing, or writes it to stdout.
+fn emit_into(capture: &mut Option<String>, line: &str) {
+    match capture.as_mut() {
+        Some(buffer) => {
+            buffer.push_str(line);
+            buffer.push('\n');
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            let _ = writeln!(stdout, "{}", line);
+        }
+    }
+}
+
 pub fn print_json<T: Serialize>(data: &T) {
+    if is_quiet() {
+        match serde_json::to_value(data) {
+            Ok(value) => print_ids(&value),
+            Err(e) => eprintln!("Error serializing JSON: {}", e),
+        }
+        return;
+    }
+
     match serde_json::to_string_pretty(data) {
-        Ok(json) => println!("{}", json),
+        Ok(json) => emit(&json),
         Err(e) => eprintln!("Error serializing JSON: {}", e),
     }
 }
 
+fn print_ids(value: &Value) {
+    for id in extract_ids(value) {
+        emit(&id);
+    }
+}
+
+fn extract_ids(value: &Value) -> Vec<String> {
+    let data = value.get("data").unwrap_or(value);
+    match data {
+        Value::Array(items) => items.iter().filter_map(resource_id).collect(),
+        Value::Object(_) => resource_id(data).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn resource_id(item: &Value) -> Option<String> {
+    // Blocklist entries are identified by their IP or hostname rather than an ID.
+    let id = ["id", "ip", "hostname"]
+        .iter()
+        .find_map(|key| item.get(*key).filter(|v| !v.is_null()))
+        .unwrap_or(item);
+
+    match id {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Prints a status message. Once a dry run has recorded a request, the
+/// command's messages would claim it was sent, so they are left out and
+/// `main` sums up what would have happened instead.
 pub fn print_message(message: &str) {
-    println!("{}", message);
+    if is_quiet() || api::recorded_requests() > 0 {
+        return;
+    }
+    emit(message);
 }
 
 pub fn print_error(message: &str) {
@@ -46,17 +304,22 @@ pub fn print_table(headers: Vec<&str>, rows: Vec<Vec<String>>) {
     table.set_header(headers);
 
     for row in rows {
-        table.add_row(row);
+        table.add_row(row.iter().map(|cell| dim_placeholder(cell)));
     }
 
-    println!("{}", table);
+    emit(&table.to_string());
 }
 
 pub fn print_key_value(pairs: Vec<(&str, String)>) {
     let max_key_len = pairs.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
 
     for (key, value) in pairs {
-        println!("{:width$}  {}", key, value, width = max_key_len);
+        emit(&format!(
+            "{:width$}  {}",
+            key,
+            dim_placeholder(&value),
+            width = max_key_len
+        ));
     }
 }
 
@@ -67,6 +330,82 @@ pub fn format_option<T: std::fmt::Display>(opt: &Option<T>) -> String {
     }
 }
 
+pub fn format_timestamp(opt: &Option<String>) -> String {
+    let Some(raw) = opt else {
+        return "-".to_string();
+    };
+    let Ok(ts) = raw.parse::<Timestamp>() else {
+        return raw.clone();
+    };
+
+    match TIME_DISPLAY.get() {
+        Some(display) if display.absolute => format_absolute(ts, &display.tz),
+        _ => format_relative(ts, Timestamp::now()),
+    }
+}
+
+fn format_absolute(ts: Timestamp, tz: &TimeZone) -> String {
+    ts.to_zoned(tz.clone())
+        .strftime("%Y-%m-%d %H:%M:%S %Z")
+        .to_string()
+}
+
+fn format_relative(ts: Timestamp, now: Timestamp) -> String {
+    let seconds = now.as_second() - ts.as_second();
+    let elapsed = seconds.unsigned_abs();
+
+    if elapsed < 45 {
+        return "just now".to_string();
+    }
+
+    let (amount, unit) = match elapsed {
+        0..=5_399 => ((elapsed + 30) / 60, "minute"),
+        5_400..=129_599 => ((elapsed + 1_800) / 3_600, "hour"),
+        129_600..=2_591_999 => ((elapsed + 43_200) / 86_400, "day"),
+        2_592_000..=31_535_999 => ((elapsed + 1_296_000) / 2_592_000, "month"),
+        _ => ((elapsed + 15_768_000) / 31_536_000, "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    if seconds < 0 {
+        format!("in {} {}{}", amount, unit, plural)
+    } else {
+        format!("{} {}{} ago", amount, unit, plural)
+    }
+}
+
+pub fn format_bytes(opt: &Option<u64>) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+    let Some(bytes) = *opt else {
+        return "-".to_string();
+    };
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+pub fn format_duration_ms(opt: &Option<u64>) -> String {
+    let Some(ms) = *opt else {
+        return "-".to_string();
+    };
+
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1_000..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        60_000..=3_599_999 => format!("{}m {}s", ms / 60_000, (ms % 60_000) / 1000),
+        _ => format!("{}h {}m", ms / 3_600_000, (ms % 3_600_000) / 60_000),
+    }
+}
+
 pub fn format_bool(b: bool) -> String {
     if b {
         "Yes".to_string()
@@ -84,8 +423,11 @@ pub fn extract_pagination(value: &Value) -> Option<(u64, u64, u64)> {
 }
 
 pub fn print_pagination(current_page: u64, last_page: u64, total: u64) {
-    if last_page > 1 {
-        println!("\nPage {} of {} ({} total)", current_page, last_page, total);
+    if last_page > 1 && !is_quiet() {
+        emit(&format!(
+            "\nPage {} of {} ({} total)",
+            current_page, last_page, total
+        ));
     }
 }
 
@@ -105,6 +447,114 @@ mod tests {
         assert_eq!(OutputFormat::detect(false, true), OutputFormat::Table);
     }
 
+    #[test]
+    fn test_color_choice_explicit() {
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+
+    #[test]
+    fn test_status_color_palette() {
+        assert_eq!(status_color("active"), Some(GREEN));
+        assert_eq!(status_color("Provisioning"), Some(YELLOW));
+        assert_eq!(status_color("failed"), Some(RED));
+        assert_eq!(status_color("suspended"), Some(RED));
+        assert_eq!(status_color("unknown"), None);
+    }
+
+    #[test]
+    fn test_level_color_palette() {
+        assert_eq!(level_color("ERROR"), Some(RED));
+        assert_eq!(level_color("warning"), Some(YELLOW));
+        assert_eq!(level_color("info"), Some(GREEN));
+        assert_eq!(level_color("debug"), Some(DIM));
+        assert!(!is_log_level("GET /index.php"));
+    }
+
+    #[test]
+    fn test_format_status_plain_when_disabled() {
+        assert_eq!(format_status("active"), "active");
+        assert_eq!(format_status("-"), "-");
+    }
+
+    #[test]
+    fn test_extract_ids_list() {
+        let value = json!({
+            "data": [{"id": "site-1"}, {"id": 42}, {"name": "no id"}],
+            "meta": {"current_page": 1, "last_page": 1, "total": 3}
+        });
+        assert_eq!(extract_ids(&value), vec!["site-1", "42"]);
+    }
+
+    #[test]
+    fn test_extract_ids_single_resource() {
+        let value = json!({"data": {"id": "env-1", "name": "staging"}});
+        assert_eq!(extract_ids(&value), vec!["env-1"]);
+    }
+
+    #[test]
+    fn test_extract_ids_blocklist_and_scalars() {
+        let value = json!({"data": [{"ip": "10.0.0.1"}, {"hostname": "spam.example"}]});
+        assert_eq!(extract_ids(&value), vec!["10.0.0.1", "spam.example"]);
+
+        let value = json!({"data": ["8.2", "8.3"]});
+        assert_eq!(extract_ids(&value), vec!["8.2", "8.3"]);
+    }
+
+    #[test]
+    fn test_extract_ids_no_data() {
+        assert!(extract_ids(&json!({"message": "Deleted"})).is_empty());
+        assert!(extract_ids(&json!({"data": null})).is_empty());
+    }
+
+    #[test]
+    fn test_capture_collects_output() {
+        // A local buffer keeps this independent of the global capture,
+        // which other tests running in parallel may print into.
+        let mut capture = Some(String::new());
+        emit_into(&mut capture, "ID  abc");
+        emit_into(&mut capture, "done");
+        assert_eq!(capture.as_deref(), Some("ID  abc\ndone\n"));
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("vector-output-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("inventory.json");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new", false).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("vector-private-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".env");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"DB_PASSWORD=secret\n", true).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_missing_directory() {
+        let path = std::env::temp_dir()
+            .join("vector-output-test-missing")
+            .join("out.json");
+        assert!(write_atomic(&path, b"data", false).is_err());
+    }
+
     #[test]
     fn test_format_option_some() {
         assert_eq!(format_option(&Some("value")), "value");
@@ -116,6 +566,79 @@ mod tests {
         assert_eq!(format_option::<String>(&None), "-");
     }
 
+    #[test]
+    fn test_format_timestamp_none_and_unparseable() {
+        assert_eq!(format_timestamp(&None), "-");
+        assert_eq!(
+            format_timestamp(&Some("not a date".to_string())),
+            "not a date"
+        );
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now: Timestamp = "2026-10-16T12:00:00Z".parse().unwrap();
+        let at = |s: &str| s.parse::<Timestamp>().unwrap();
+
+        assert_eq!(format_relative(at("2026-10-16T11:59:50Z"), now), "just now");
+        assert_eq!(
+            format_relative(at("2026-10-16T11:59:00Z"), now),
+            "1 minute ago"
+        );
+        assert_eq!(
+            format_relative(at("2026-10-16T09:00:00Z"), now),
+            "3 hours ago"
+        );
+        assert_eq!(
+            format_relative(at("2026-10-13T12:00:00Z"), now),
+            "3 days ago"
+        );
+        assert_eq!(
+            format_relative(at("2026-06-16T12:00:00Z"), now),
+            "4 months ago"
+        );
+        assert_eq!(
+            format_relative(at("2024-10-16T12:00:00Z"), now),
+            "2 years ago"
+        );
+        assert_eq!(
+            format_relative(at("2026-10-16T14:00:00Z"), now),
+            "in 2 hours"
+        );
+    }
+
+    #[test]
+    fn test_format_absolute() {
+        let ts: Timestamp = "2026-10-16T09:00:00Z".parse().unwrap();
+        assert_eq!(
+            format_absolute(ts, &TimeZone::UTC),
+            "2026-10-16 09:00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn test_init_time_display_rejects_unknown_zone() {
+        assert!(init_time_display(true, Some("Not/AZone")).is_err());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(&None), "-");
+        assert_eq!(format_bytes(&Some(512)), "512 B");
+        assert_eq!(format_bytes(&Some(1536)), "1.5 KB");
+        assert_eq!(format_bytes(&Some(5 * 1024 * 1024)), "5.0 MB");
+        assert_eq!(format_bytes(&Some(3 * 1024 * 1024 * 1024)), "3.0 GB");
+    }
+
+    #[test]
+    fn test_format_duration_ms() {
+        assert_eq!(format_duration_ms(&None), "-");
+        assert_eq!(format_duration_ms(&Some(850)), "850ms");
+        assert_eq!(format_duration_ms(&Some(12_345)), "12.3s");
+        assert_eq!(format_duration_ms(&Some(252_000)), "4m 12s");
+        assert_eq!(format_duration_ms(&Some(3_720_000)), "1h 2m");
+    }
+
     #[test]
     fn test_format_bool() {
         assert_eq!(format_bool(true), "Yes");
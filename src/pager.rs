@@ -0,0 +1,97 @@
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use terminal_size::{Height, terminal_size};
+
+use crate::output::{start_capture, take_capture};
+
+const DEFAULT_PAGER: &str = "less -FRX";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Starts buffering stdout when paging applies: stdout is a TTY and paging
+/// has not been turned off with `--no-pager` or the `pager` config key.
+/// Callers only set this up for commands whose output is bounded, since
+/// nothing is shown until `finish`.
+pub fn setup(no_pager: bool, config_enabled: Option<bool>) {
+    if no_pager || config_enabled == Some(false) || !atty::is(atty::Stream::Stdout) {
+        return;
+    }
+    if pager_command().is_some() {
+        ACTIVE.store(true, Ordering::Relaxed);
+        start_capture();
+    }
+}
+
+/// Writes buffered output, piping it through `$PAGER` when it does not fit
+/// on the terminal.
+pub fn finish() {
+    if !ACTIVE.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let Some(output) = take_capture() else {
+        return;
+    };
+
+    let height = terminal_size().map(|(_, Height(h))| h as usize);
+    if let (Some(height), Some(command)) = (height, pager_command())
+        && exceeds_height(&output, height)
+        && run_pager(&command, &output)
+    {
+        return;
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(output.as_bytes());
+}
+
+fn pager_command() -> Option<String> {
+    let command = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let command = command.trim();
+    if command.is_empty() || command == "cat" {
+        None
+    } else {
+        Some(command.to_string())
+    }
+}
+
+fn exceeds_height(output: &str, height: usize) -> bool {
+    // Leave room for the shell prompt after the output.
+    output.lines().count() >= height.saturating_sub(1)
+}
+
+fn run_pager(command: &str, output: &str) -> bool {
+    let mut parts = command.split_whitespace();
+    let Some(program) = parts.next() else {
+        return false;
+    };
+
+    let Ok(mut child) = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything.
+        let _ = stdin.write_all(output.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exceeds_height() {
+        assert!(!exceeds_height("a\nb\n", 24));
+        assert!(exceeds_height(&"line\n".repeat(23), 24));
+        assert!(exceeds_height(&"line\n".repeat(100), 24));
+    }
+}
@@ -0,0 +1,369 @@
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::api::ApiError;
+
+/// Default key files, in the order ssh itself prefers them.
+const DEFAULT_KEY_FILES: &[&str] = &["id_ed25519.pub", "id_ecdsa.pub", "id_rsa.pub"];
+
+const MIN_RSA_BITS: usize = 2048;
+
+/// Where to read a public key from.
+#[derive(Debug)]
+pub enum KeySource {
+    /// The key text itself.
+    Inline(String),
+    File(PathBuf),
+    /// A key loaded in ssh-agent, optionally matched by fingerprint or
+    /// comment.
+    Agent(Option<String>),
+    /// The first of `~/.ssh/id_ed25519.pub` and friends that exists.
+    Default,
+}
+
+/// A validated OpenSSH public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    pub algorithm: String,
+    blob: Vec<u8>,
+    pub comment: Option<String>,
+}
+
+impl PublicKey {
+    /// Parses and validates a key in `authorized_keys` form:
+    /// `<type> <base64> [comment]`.
+    pub fn parse(text: &str) -> Result<Self, ApiError> {
+        let mut parts = text.split_whitespace();
+        let (Some(algorithm), Some(encoded)) = (parts.next(), parts.next()) else {
+            return Err(invalid("expected '<type> <base64 key> [comment]'"));
+        };
+        let comment = parts.collect::<Vec<_>>().join(" ");
+
+        let blob = STANDARD
+            .decode(encoded)
+            .or_else(|_| STANDARD_NO_PAD.decode(encoded))
+            .map_err(|_| invalid("key data is not valid base64"))?;
+        validate_blob(algorithm, &blob)?;
+
+        Ok(Self {
+            algorithm: algorithm.to_string(),
+            blob,
+            comment: (!comment.is_empty()).then_some(comment),
+        })
+    }
+
+    /// The fingerprint as shown by `ssh-keygen -l`, e.g. `SHA256:...`.
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "SHA256:{}",
+            STANDARD_NO_PAD.encode(Sha256::digest(&self.blob))
+        )
+    }
+
+    /// The key in `authorized_keys` form, as sent to the API.
+    pub fn to_openssh(&self) -> String {
+        let mut text = format!("{} {}", self.algorithm, STANDARD.encode(&self.blob));
+        if let Some(comment) = &self.comment {
+            text.push(' ');
+            text.push_str(comment);
+        }
+        text
+    }
+}
+
+fn invalid(reason: &str) -> ApiError {
+    ApiError::ValidationError(format!("Invalid SSH public key: {}", reason))
+}
+
+/// Reads one length-prefixed field of the SSH wire format.
+fn read_field<'a>(data: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let field = data.get(4..4 + len)?;
+    *data = &data[4 + len..];
+    Some(field)
+}
+
+/// Checks that the key blob matches its declared type and has a sensible
+/// size for it.
+fn validate_blob(algorithm: &str, blob: &[u8]) -> Result<(), ApiError> {
+    let truncated = || invalid("key data is truncated");
+    let mut data = blob;
+    let declared = read_field(&mut data).ok_or_else(truncated)?;
+    if declared != algorithm.as_bytes() {
+        return Err(invalid(&format!(
+            "key data is for '{}', not '{}'",
+            String::from_utf8_lossy(declared),
+            algorithm
+        )));
+    }
+
+    match algorithm {
+        "ssh-ed25519" | "sk-ssh-ed25519@openssh.com" => {
+            let key = read_field(&mut data).ok_or_else(truncated)?;
+            if key.len() != 32 {
+                return Err(invalid("Ed25519 keys must be 32 bytes"));
+            }
+        }
+        "ssh-rsa" => {
+            let _exponent = read_field(&mut data).ok_or_else(truncated)?;
+            let modulus = read_field(&mut data).ok_or_else(truncated)?;
+            let bits = rsa_bits(modulus);
+            if bits < MIN_RSA_BITS {
+                return Err(invalid(&format!(
+                    "RSA keys must be at least {} bits, this one is {}",
+                    MIN_RSA_BITS, bits
+                )));
+            }
+        }
+        "ecdsa-sha2-nistp256"
+        | "ecdsa-sha2-nistp384"
+        | "ecdsa-sha2-nistp521"
+        | "sk-ecdsa-sha2-nistp256@openssh.com" => {
+            let curve = read_field(&mut data).ok_or_else(truncated)?;
+            let point = read_field(&mut data).ok_or_else(truncated)?;
+            let expected = match curve {
+                b"nistp256" => 65,
+                b"nistp384" => 97,
+                b"nistp521" => 133,
+                _ => return Err(invalid("unknown ECDSA curve")),
+            };
+            if point.len() != expected || point[0] != 0x04 {
+                return Err(invalid("ECDSA key has the wrong length for its curve"));
+            }
+        }
+        other => {
+            return Err(invalid(&format!(
+                "unsupported key type '{}'. Use ssh-ed25519, ecdsa-sha2-* or ssh-rsa.",
+                other
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn rsa_bits(modulus: &[u8]) -> usize {
+    let start = modulus
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(modulus.len());
+    match modulus.get(start) {
+        Some(first) => (modulus.len() - start) * 8 - first.leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+/// Loads and validates a public key from `source`.
+pub fn load(source: KeySource) -> Result<PublicKey, ApiError> {
+    match source {
+        KeySource::Inline(text) => PublicKey::parse(&text),
+        KeySource::File(path) => read_key_file(&path),
+        KeySource::Default => {
+            let dir = dirs::home_dir()
+                .ok_or_else(|| ApiError::ConfigError("Could not determine home directory".into()))?
+                .join(".ssh");
+            let path = DEFAULT_KEY_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.exists())
+                .ok_or_else(|| {
+                    ApiError::ValidationError(format!(
+                        "No public key found in {}. Use --public-key, --public-key-file or --from-agent.",
+                        dir.display()
+                    ))
+                })?;
+            read_key_file(&path)
+        }
+        KeySource::Agent(pattern) => {
+            let keys = agent_keys()?;
+            pick_agent_key(keys, pattern.as_deref())
+        }
+    }
+}
+
+fn read_key_file(path: &Path) -> Result<PublicKey, ApiError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| ApiError::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+    if text.contains("PRIVATE KEY") {
+        return Err(ApiError::ValidationError(format!(
+            "{} is a private key. Use the .pub file instead.",
+            path.display()
+        )));
+    }
+    PublicKey::parse(text.trim())
+}
+
+/// Lists the keys loaded in ssh-agent via `ssh-add -L`.
+fn agent_keys() -> Result<Vec<PublicKey>, ApiError> {
+    let output = Command::new("ssh-add")
+        .arg("-L")
+        .output()
+        .map_err(|e| ApiError::Other(format!("Failed to run ssh-add: {}", e)))?;
+
+    // ssh-add exits with 1 when the agent has no keys and 2 when no agent
+    // is running.
+    match output.status.code() {
+        Some(0) => {}
+        Some(1) => {
+            return Err(ApiError::ValidationError(
+                "ssh-agent has no keys loaded. Add one with ssh-add.".to_string(),
+            ));
+        }
+        _ => {
+            return Err(ApiError::ConfigError(
+                "Could not connect to ssh-agent. Is SSH_AUTH_SOCK set?".to_string(),
+            ));
+        }
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(PublicKey::parse)
+        .collect()
+}
+
+/// Picks the agent key whose fingerprint or comment matches `pattern`, or
+/// the only key when no pattern is given.
+fn pick_agent_key(keys: Vec<PublicKey>, pattern: Option<&str>) -> Result<PublicKey, ApiError> {
+    let mut matches: Vec<PublicKey> = keys
+        .into_iter()
+        .filter(|key| {
+            pattern.is_none_or(|p| {
+                key.fingerprint() == p
+                    || key.fingerprint().trim_start_matches("SHA256:") == p
+                    || key.comment.as_deref().is_some_and(|c| c.contains(p))
+            })
+        })
+        .collect();
+
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => Err(ApiError::ValidationError(format!(
+            "No key in ssh-agent matches '{}'",
+            pattern.unwrap_or_default()
+        ))),
+        _ => {
+            let listed: Vec<String> = matches
+                .iter()
+                .map(|key| {
+                    format!(
+                        "  {} {}",
+                        key.fingerprint(),
+                        key.comment.as_deref().unwrap_or("")
+                    )
+                })
+                .collect();
+            Err(ApiError::ValidationError(format!(
+                "Several keys in ssh-agent match. Pass a fingerprint or comment to --from-agent:\n{}",
+                listed.join("\n")
+            )))
+        }
+    }
+}
+
+/// The SHA256 fingerprint of a key returned by the API, computed from its
+/// public key when present. Otherwise the API's own fingerprint is shown,
+/// marked `MD5:` unless it names its hash, so the two are never confused.
+pub fn display_fingerprint(key: &Value) -> String {
+    if let Some(key) = key["public_key"]
+        .as_str()
+        .and_then(|text| PublicKey::parse(text).ok())
+    {
+        return key.fingerprint();
+    }
+    match key["fingerprint"].as_str() {
+        Some(fp) if fp.starts_with("SHA256:") || fp.starts_with("MD5:") => fp.to_string(),
+        Some(fp) => format!("MD5:{}", fp),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ED25519: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKFKjQ8L1jrVE9rOgV5+IsnkgSIqSnCP4uE7174+Cw9I jane@laptop";
+    const ECDSA: &str = "ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTYAAABBBFbUAvKTWfjA8kuf82ojrvVLBxgHTibQI1H3BpMPA+Pgg93O5QLgD0xTmpZ1hNYyD0BQ6Vu2u46wDuEz/yteUAE=";
+
+    #[test]
+    fn test_parse_ed25519() {
+        let key = PublicKey::parse(ED25519).unwrap();
+        assert_eq!(key.algorithm, "ssh-ed25519");
+        assert_eq!(key.comment.as_deref(), Some("jane@laptop"));
+        assert_eq!(key.to_openssh(), ED25519);
+        // As printed by `ssh-keygen -lf`.
+        assert_eq!(
+            key.fingerprint(),
+            "SHA256:bxwSqs9dtP8/pIKU/2lgsiYXJ8CAUs9AfQhWW8rs3GA"
+        );
+    }
+
+    #[test]
+    fn test_parse_ecdsa() {
+        let key = PublicKey::parse(ECDSA).unwrap();
+        assert_eq!(key.comment, None);
+        assert_eq!(
+            key.fingerprint(),
+            "SHA256:pzYAYIa0kQig70pWUwrL3FNEdaNN5R807TUlX+owICY"
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_keys() {
+        let rsa_as_ed = ED25519.replacen("ssh-ed25519", "ssh-rsa", 1);
+        for text in [
+            "",
+            "ssh-ed25519",
+            "ssh-ed25519 not*base64",
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5",
+            "ssh-dss AAAAB3NzaC1kc3M=",
+            rsa_as_ed.as_str(),
+        ] {
+            assert!(PublicKey::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_rsa_bits() {
+        assert_eq!(rsa_bits(&[0x00, 0x80, 0x00]), 16);
+        assert_eq!(rsa_bits(&[0x01, 0xff]), 9);
+        assert_eq!(rsa_bits(&[]), 0);
+    }
+
+    #[test]
+    fn test_display_fingerprint() {
+        let computed = PublicKey::parse(ED25519).unwrap().fingerprint();
+        assert_eq!(
+            display_fingerprint(&json!({"public_key": ED25519, "fingerprint": "aa:bb"})),
+            computed
+        );
+        assert_eq!(
+            display_fingerprint(&json!({"fingerprint": "aa:bb:cc"})),
+            "MD5:aa:bb:cc"
+        );
+        assert_eq!(
+            display_fingerprint(&json!({"fingerprint": "SHA256:abc"})),
+            "SHA256:abc"
+        );
+        assert_eq!(display_fingerprint(&json!({})), "-");
+    }
+
+    #[test]
+    fn test_pick_agent_key() {
+        let first = PublicKey::parse(ED25519).unwrap();
+        let mut second = first.clone();
+        second.comment = Some("ci@build".to_string());
+
+        let keys = vec![first.clone(), second.clone()];
+        assert!(pick_agent_key(keys.clone(), None).is_err());
+        assert_eq!(pick_agent_key(keys.clone(), Some("ci")).unwrap(), second);
+        assert!(pick_agent_key(keys, Some("nobody")).is_err());
+        assert_eq!(pick_agent_key(vec![first.clone()], None).unwrap(), first);
+    }
+}
@@ -0,0 +1,156 @@
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Span, Timestamp};
+
+use crate::api::ApiError;
+use crate::output::display_time_zone;
+
+/// Converts a time argument to the UTC ISO 8601 form the API expects.
+///
+/// Accepts ISO 8601 timestamps, local datetimes such as `2026-10-16 09:00`
+/// (in the `--tz` zone, or the system zone), `now`, `today`, `yesterday`,
+/// offsets into the past such as `15m` or `2h`, and `now-1d` / `now+1h`.
+pub fn resolve(input: Option<String>, flag: &str) -> Result<Option<String>, ApiError> {
+    input
+        .map(|input| resolve_timestamp(&input, flag).map(format_utc))
+        .transpose()
+}
+
+/// Parses a time argument in any form [`resolve`] accepts.
+pub fn resolve_timestamp(input: &str, flag: &str) -> Result<Timestamp, ApiError> {
+    match parse(input, Timestamp::now(), &display_time_zone()) {
+        Some(ts) => Ok(ts),
+        None => Err(ApiError::ValidationError(format!(
+            "Invalid time for {}: '{}'. Use ISO 8601, a local datetime like \
+             '2026-10-16 09:00', or an expression like 15m, 2h, now-1d or yesterday.",
+            flag, input
+        ))),
+    }
+}
+
+fn parse(input: &str, now: Timestamp, tz: &TimeZone) -> Option<Timestamp> {
+    let input = input.trim();
+    let lower = input.to_ascii_lowercase();
+
+    match lower.as_str() {
+        "now" => return Some(now),
+        "today" => return start_of_day(now, tz, 0),
+        "yesterday" => return start_of_day(now, tz, 1),
+        _ => {}
+    }
+
+    if let Some(rest) = lower.strip_prefix("now") {
+        let offset = if let Some(amount) = rest.strip_prefix('-') {
+            -duration(amount)?
+        } else {
+            duration(rest.strip_prefix('+')?)?
+        };
+        return now.checked_add(offset).ok();
+    }
+
+    // A bare duration means that long ago.
+    if let Some(ago) = duration(&lower) {
+        return now.checked_sub(ago).ok();
+    }
+
+    if let Ok(ts) = input.parse::<Timestamp>() {
+        return Some(ts);
+    }
+    if let Ok(datetime) = input.parse::<DateTime>() {
+        return datetime.to_zoned(tz.clone()).ok().map(|z| z.timestamp());
+    }
+    if let Ok(date) = input.parse::<Date>() {
+        return date.to_zoned(tz.clone()).ok().map(|z| z.timestamp());
+    }
+
+    None
+}
+
+/// Parses `<number><unit>` where the unit is one of s, m, h, d or w.
+fn duration(expr: &str) -> Option<SignedDuration> {
+    let split = expr.find(|c: char| !c.is_ascii_digit())?;
+    let (digits, unit) = expr.split_at(split);
+    let amount: i64 = digits.parse().ok()?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return None,
+    };
+    Some(SignedDuration::from_secs(amount.checked_mul(seconds)?))
+}
+
+fn start_of_day(now: Timestamp, tz: &TimeZone, days_back: i64) -> Option<Timestamp> {
+    let date = now
+        .to_zoned(tz.clone())
+        .date()
+        .checked_sub(Span::new().days(days_back))
+        .ok()?;
+    date.to_zoned(tz.clone()).ok().map(|z| z.timestamp())
+}
+
+fn format_utc(ts: Timestamp) -> String {
+    ts.strftime("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve_at(input: &str, tz: &TimeZone) -> Option<String> {
+        let now: Timestamp = "2026-10-16T12:30:00Z".parse().unwrap();
+        parse(input, now, tz).map(format_utc)
+    }
+
+    #[test]
+    fn test_relative_expressions() {
+        let utc = TimeZone::UTC;
+        assert_eq!(resolve_at("now", &utc).unwrap(), "2026-10-16T12:30:00Z");
+        assert_eq!(resolve_at("15m", &utc).unwrap(), "2026-10-16T12:15:00Z");
+        assert_eq!(resolve_at("2h", &utc).unwrap(), "2026-10-16T10:30:00Z");
+        assert_eq!(resolve_at("now-1d", &utc).unwrap(), "2026-10-15T12:30:00Z");
+        assert_eq!(resolve_at("NOW+30s", &utc).unwrap(), "2026-10-16T12:30:30Z");
+        assert_eq!(resolve_at("1w", &utc).unwrap(), "2026-10-09T12:30:00Z");
+    }
+
+    #[test]
+    fn test_days_use_time_zone() {
+        let tz = TimeZone::get("America/New_York").unwrap();
+        assert_eq!(resolve_at("today", &tz).unwrap(), "2026-10-16T04:00:00Z");
+        assert_eq!(
+            resolve_at("yesterday", &tz).unwrap(),
+            "2026-10-15T04:00:00Z"
+        );
+        assert_eq!(
+            resolve_at("2026-10-16 09:00", &tz).unwrap(),
+            "2026-10-16T13:00:00Z"
+        );
+        assert_eq!(
+            resolve_at("2026-10-16", &tz).unwrap(),
+            "2026-10-16T04:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_absolute_timestamps() {
+        let tz = TimeZone::get("America/New_York").unwrap();
+        assert_eq!(
+            resolve_at("2026-10-16T09:00:00Z", &tz).unwrap(),
+            "2026-10-16T09:00:00Z"
+        );
+        assert_eq!(
+            resolve_at("2026-10-16T09:00:00+02:00", &tz).unwrap(),
+            "2026-10-16T07:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        let utc = TimeZone::UTC;
+        for input in ["", "soon", "15", "2x", "now-", "now*1d", "2026-13-01"] {
+            assert_eq!(resolve_at(input, &utc), None, "{}", input);
+        }
+    }
+}
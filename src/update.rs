@@ -0,0 +1,126 @@
+use jiff::Timestamp;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::api::{ApiClient, ApiError};
+use crate::diff::{self, Field};
+
+/// The resource as it was before an update, and the API's response.
+pub struct Updated {
+    pub before: Value,
+    pub response: Value,
+}
+
+fn updated_at(resource: &Value) -> Option<Timestamp> {
+    resource["updated_at"].as_str()?.parse().ok()
+}
+
+fn version(resource: &Value) -> String {
+    resource["updated_at"]
+        .as_str()
+        .unwrap_or("an unknown time")
+        .to_string()
+}
+
+/// Fails when `current` was modified after `since`, or when its
+/// modification time is unknown and so cannot be checked.
+fn check_unmodified(what: &str, current: &Value, since: Timestamp) -> Result<(), ApiError> {
+    match updated_at(current) {
+        Some(modified) if modified > since => Err(ApiError::Conflict(format!(
+            "{} was modified at {}, after {} given to --if-unmodified-since. \
+             Review the current version and retry with a newer time.",
+            what,
+            version(current),
+            since.strftime("%Y-%m-%dT%H:%M:%SZ")
+        ))),
+        Some(_) => Ok(()),
+        None => Err(ApiError::Other(format!(
+            "Cannot check --if-unmodified-since: {} has no valid updated_at ({}). Nothing was applied.",
+            what,
+            version(current)
+        ))),
+    }
+}
+
+/// Describes how two versions of a resource differ in `fields`.
+fn describe_conflict(what: &str, read: &Value, latest: &Value, fields: &[Field]) -> String {
+    let changed: Vec<String> = diff::changes(read, latest, &json!({}), fields, &[])
+        .into_iter()
+        .map(|c| format!("{} ({} → {})", c.label, c.before, c.after))
+        .collect();
+    let mut message = format!(
+        "{} changed while it was being updated (read version from {}, current version from {}).",
+        what,
+        version(read),
+        version(latest)
+    );
+    if !changed.is_empty() {
+        message.push_str(&format!(" Changed fields: {}.", changed.join(", ")));
+    }
+    message.push_str(" Nothing was applied; review the changes and retry.");
+    message
+}
+
+/// PUTs `body` to `path` only if the resource has not changed underneath
+/// us. The current version is read first and checked against
+/// `unmodified_since`; the update carries `If-Match` when the API sends an
+/// ETag, and a rejection is reported as a conflict listing both versions.
+/// `what` names the resource in messages, e.g. "Environment e1".
+pub fn put_unmodified<B: Serialize>(
+    client: &ApiClient,
+    what: &str,
+    path: &str,
+    body: &B,
+    unmodified_since: Option<Timestamp>,
+    fields: &[Field],
+) -> Result<Updated, ApiError> {
+    let (before, etag): (Value, Option<String>) = client.get_with_etag(path)?;
+    if let Some(since) = unmodified_since {
+        check_unmodified(what, &before["data"], since)?;
+    }
+
+    match client.put_if_match(path, body, etag.as_deref()) {
+        Ok(response) => Ok(Updated { before, response }),
+        Err(ApiError::Conflict(_)) if etag.is_some() => {
+            let latest: Value = client.get(path)?;
+            Err(ApiError::Conflict(describe_conflict(
+                what,
+                &before["data"],
+                &latest["data"],
+                fields,
+            )))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_unmodified() {
+        let current = json!({"updated_at": "2026-10-18T10:00:00.000000Z"});
+        let before: Timestamp = "2026-10-18T09:00:00Z".parse().unwrap();
+        let after: Timestamp = "2026-10-18T11:00:00Z".parse().unwrap();
+
+        let err = check_unmodified("Environment e1", &current, before).unwrap_err();
+        assert!(matches!(err, ApiError::Conflict(_)));
+        assert!(err.to_string().contains("2026-10-18T10:00:00.000000Z"));
+        assert!(check_unmodified("Environment e1", &current, after).is_ok());
+        assert!(check_unmodified("Environment e1", &json!({}), before).is_err());
+        let garbled = json!({"updated_at": "yesterday"});
+        let err = check_unmodified("Environment e1", &garbled, after).unwrap_err();
+        assert!(err.to_string().contains("yesterday"));
+    }
+
+    #[test]
+    fn test_describe_conflict() {
+        let read = json!({"name": "staging", "updated_at": "2026-10-18T09:00:00Z"});
+        let latest = json!({"name": "qa", "updated_at": "2026-10-18T10:00:00Z"});
+        let message = describe_conflict("Environment e1", &read, &latest, &[("Name", "name")]);
+        assert!(message.contains("read version from 2026-10-18T09:00:00Z"));
+        assert!(message.contains("current version from 2026-10-18T10:00:00Z"));
+        assert!(message.contains("Name (staging → qa)"));
+    }
+}
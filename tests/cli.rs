@@ -1,4 +1,8 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 
 fn vector_cmd() -> Command {
     Command::new(env!("CARGO_BIN_EXE_vector"))
@@ -11,6 +15,67 @@ fn nonexistent_config_dir() -> String {
         .to_string()
 }
 
+/// Serves one canned response per request, in order, on a local port.
+/// Returns a config directory pointing the CLI at it and the requests
+/// received, each as its request line followed by its headers (lowercase
+/// names, as sent by the client).
+fn mock_api(name: &str, responses: Vec<(u16, &'static str)>) -> (PathBuf, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let received = Arc::clone(&requests);
+    std::thread::spawn(move || {
+        for (status, body) in responses {
+            let Ok((stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap_or(0);
+                }
+                head.push_str(&line);
+            }
+            let mut body_in = vec![0; length];
+            let _ = reader.read_exact(&mut body_in);
+            received.lock().unwrap().push(head);
+
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = reader.get_mut().write_all(response.as_bytes());
+        }
+    });
+
+    let dir = std::env::temp_dir().join(format!("vector-test-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.json"),
+        format!(r#"{{"api_url": "http://127.0.0.1:{}"}}"#, port),
+    )
+    .unwrap();
+    (dir, requests)
+}
+
+fn request_lines(requests: &Mutex<Vec<String>>) -> Vec<String> {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|r| r.lines().next().unwrap_or_default().to_string())
+        .collect()
+}
+
 #[test]
 fn test_help() {
     let output = vector_cmd().arg("--help").output().expect("Failed to run");
@@ -128,6 +193,7 @@ fn test_mcp_setup_help() {
 }
 
 #[test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn test_mcp_setup_requires_auth() {
     let output = vector_cmd()
         .args(["mcp", "setup"])
@@ -140,6 +206,7 @@ fn test_mcp_setup_requires_auth() {
 }
 
 #[test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn test_auth_status_not_logged_in() {
     let output = vector_cmd()
         .args(["auth", "status", "--json"])
@@ -153,6 +220,7 @@ fn test_auth_status_not_logged_in() {
 }
 
 #[test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn test_site_list_requires_auth() {
     let output = vector_cmd()
         .args(["site", "list"])
@@ -174,6 +242,7 @@ fn test_invalid_subcommand() {
 }
 
 #[test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn test_json_flag() {
     let output = vector_cmd()
         .args(["--json", "auth", "status"])
@@ -184,3 +253,479 @@ fn test_json_flag() {
     // Should be valid JSON
     assert!(serde_json::from_str::<serde_json::Value>(&stdout).is_ok());
 }
+
+#[test]
+fn test_quiet_flag() {
+    let output = vector_cmd().arg("--help").output().expect("Failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--quiet"));
+
+    let output = vector_cmd()
+        .args(["site", "list", "-q"])
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env_remove("VECTOR_API_KEY")
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(2)); // EXIT_AUTH_ERROR
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_output_file_written_on_success() {
+    let path = std::env::temp_dir().join(format!("vector-test-out-{}.json", std::process::id()));
+    let output = vector_cmd()
+        .args(["auth", "status", "--output-file"])
+        .arg(&path)
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env_remove("VECTOR_API_KEY")
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(serde_json::from_str::<serde_json::Value>(&content).is_ok());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_output_file_untouched_on_failure() {
+    let path = std::env::temp_dir().join(format!("vector-test-keep-{}.json", std::process::id()));
+    std::fs::write(&path, "previous").unwrap();
+    let output = vector_cmd()
+        .args(["site", "list", "--output-file"])
+        .arg(&path)
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env_remove("VECTOR_API_KEY")
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(2)); // EXIT_AUTH_ERROR
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not logged in"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_output_file_rejected_for_streaming_commands() {
+    let path = std::env::temp_dir().join(format!("vector-test-ssh-{}.json", std::process::id()));
+    for args in [
+        &["site", "ssh", "site-1"][..],
+        &["site", "sftp", "site-1"],
+        &["site", "logs", "site-1", "--follow"],
+    ] {
+        let output = vector_cmd()
+            .args(args)
+            .arg("--output-file")
+            .arg(&path)
+            .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+            .env_remove("VECTOR_API_KEY")
+            .output()
+            .expect("Failed to run");
+        assert_eq!(output.status.code(), Some(3)); // EXIT_VALIDATION_ERROR
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--output-file cannot be used"));
+        assert!(!path.exists());
+    }
+}
+
+#[test]
+fn test_site_logs_interval_requires_follow() {
+    let output = vector_cmd()
+        .args(["site", "logs", "site-1", "--interval", "2"])
+        .output()
+        .expect("Failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--follow"));
+}
+
+#[test]
+fn test_site_logs_export_resume_conflicts_with_filters() {
+    let output = vector_cmd()
+        .args([
+            "site",
+            "logs",
+            "export",
+            "site-1",
+            "--output",
+            "logs.ndjson",
+            "--resume",
+            "--level",
+            "error",
+        ])
+        .output()
+        .expect("Failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--resume"));
+}
+
+#[test]
+fn test_invalid_time_expression() {
+    let output = vector_cmd()
+        .args(["event", "list", "--from", "soon"])
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env("VECTOR_API_KEY", "test-token")
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(3)); // EXIT_VALIDATION_ERROR
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid time for --from"));
+}
+
+#[test]
+fn test_site_bulk_selection_conflicts_with_id() {
+    let output = vector_cmd()
+        .args(["site", "suspend", "site-1", "--tag", "legacy"])
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--tag"));
+}
+
+#[test]
+fn test_site_purge_cache_sitemap_conflicts_with_url() {
+    let output = vector_cmd()
+        .args([
+            "site",
+            "purge-cache",
+            "site-1",
+            "--sitemap",
+            "sitemap.xml",
+            "--url",
+            "https://example.com/",
+        ])
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_site_ssh_config_requires_selection() {
+    let output = vector_cmd()
+        .args(["site", "ssh-config"])
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_env_delete_refuses_without_tty_or_yes() {
+    let output = vector_cmd()
+        .args(["env", "delete", "e1"])
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env("VECTOR_API_KEY", "test-key")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(3)); // EXIT_VALIDATION_ERROR
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--yes"));
+}
+
+#[test]
+fn test_production_env_delete_requires_confirm_production() {
+    let env = r#"{"data": {"id": "e1", "name": "live", "is_production": true}}"#;
+    let (dir, requests) = mock_api("guard", vec![(200, env)]);
+    let output = vector_cmd()
+        .args(["env", "delete", "e1", "--yes"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(3)); // EXIT_VALIDATION_ERROR
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--confirm-production"));
+    assert_eq!(
+        request_lines(&requests),
+        ["GET /api/v1/vector/environments/e1 HTTP/1.1"]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_env_delete_with_confirm_production_skips_guard() {
+    let (dir, requests) = mock_api("confirmed", vec![(200, r#"{"data": {}}"#)]);
+    let output = vector_cmd()
+        .args([
+            "env",
+            "delete",
+            "e1",
+            "--yes",
+            "--confirm-production",
+            "--json",
+        ])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    assert_eq!(
+        request_lines(&requests),
+        ["DELETE /api/v1/vector/environments/e1 HTTP/1.1"]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_env_update_sends_if_match() {
+    let env = r#"{"data": {"id": "e1", "name": "old", "updated_at": "2026-10-18T10:00:00Z"}}"#;
+    let (dir, requests) = mock_api("if-match", vec![(200, env), (200, env)]);
+    let output = vector_cmd()
+        .args(["env", "update", "e1", "--name", "new", "--json"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].starts_with("PUT /api/v1/vector/environments/e1 "));
+    assert!(
+        requests[1]
+            .to_ascii_lowercase()
+            .contains("if-match: \"v1\"")
+    );
+    drop(requests);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_env_update_precondition_failed_is_conflict() {
+    let env = r#"{"data": {"id": "e1", "name": "old", "updated_at": "2026-10-18T10:00:00Z"}}"#;
+    let changed =
+        r#"{"data": {"id": "e1", "name": "other", "updated_at": "2026-10-18T11:00:00Z"}}"#;
+    let (dir, _) = mock_api(
+        "precondition",
+        vec![
+            (200, env),
+            (412, r#"{"message": "changed"}"#),
+            (200, changed),
+        ],
+    );
+    let output = vector_cmd()
+        .args(["env", "update", "e1", "--name", "new"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(7)); // EXIT_CONFLICT
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_dry_run_records_delete_without_sending() {
+    let output = vector_cmd()
+        .args(["--dry-run", "webhook", "delete", "w1", "--no-json"])
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env("VECTOR_API_KEY", "test-key")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("DRY RUN: DELETE /api/v1/vector/webhooks/w1"));
+    assert!(stderr.contains("would send the 1 request above"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("successfully"));
+}
+
+#[test]
+fn test_dry_run_note_only_when_requests_recorded() {
+    let output = vector_cmd()
+        .args(["--dry-run", "auth", "status"])
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env_remove("VECTOR_API_KEY")
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Dry run"));
+}
+
+#[test]
+fn test_dry_run_skips_wait() {
+    let output = vector_cmd()
+        .args(["--dry-run", "site", "create", "--customer-id", "acme"])
+        .args(["--dev-php-version", "8.3", "--wait", "--no-json"])
+        .env("VECTOR_CONFIG_DIR", nonexistent_config_dir())
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Dry run: not waiting."));
+    assert!(stderr.contains("DRY RUN: POST /api/v1/vector/sites"));
+}
+
+#[test]
+fn test_blueprint_stops_and_rolls_back_when_id_is_missing() {
+    let (dir, requests) = mock_api(
+        "blueprint",
+        vec![
+            (201, r#"{"data": {"id": "s1"}}"#),
+            (201, r#"{"data": {}}"#),
+            (200, r#"{"data": {}}"#),
+        ],
+    );
+    let blueprint = dir.join("site.yaml");
+    std::fs::write(
+        &blueprint,
+        "site:\n  customer_id: acme\n  dev_php_version: \"8.3\"\n\
+         environments:\n  - name: production\n    custom_domain: acme.example.com\n    \
+         php_version: \"8.3\"\n    secrets:\n      - key: TOKEN\n        value: abc\n",
+    )
+    .unwrap();
+    let output = vector_cmd()
+        .args(["site", "create", "--from"])
+        .arg(&blueprint)
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("did not return an ID"));
+    assert_eq!(
+        request_lines(&requests),
+        [
+            "POST /api/v1/vector/sites HTTP/1.1",
+            "POST /api/v1/vector/sites/s1/environments HTTP/1.1",
+            "DELETE /api/v1/vector/sites/s1 HTTP/1.1",
+        ]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_site_create_wait_requires_returned_id() {
+    let (dir, requests) = mock_api("create-wait", vec![(201, r#"{"data": {}}"#)]);
+    let output = vector_cmd()
+        .args(["site", "create", "--customer-id", "acme"])
+        .args(["--dev-php-version", "8.3", "--wait"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("did not return its ID"));
+    assert_eq!(
+        request_lines(&requests),
+        ["POST /api/v1/vector/sites HTTP/1.1"]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_env_tag_matching_tag_lists_environments_per_site() {
+    let (dir, requests) = mock_api(
+        "env-tag",
+        vec![
+            (200, r#"{"data": [{"id": "s1"}]}"#),
+            (
+                200,
+                r#"{"data": [{"id": "e1", "tags": ["legacy"]}, {"id": "e2", "tags": []}]}"#,
+            ),
+            (200, r#"{"data": {"id": "e1", "tags": ["legacy"]}}"#),
+            (
+                200,
+                r#"{"data": {"id": "e1", "tags": ["legacy", "upgrade"]}}"#,
+            ),
+        ],
+    );
+    let output = vector_cmd()
+        .args(["env", "tag", "add", "--matching-tag", "legacy", "upgrade"])
+        .args(["--json"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    assert_eq!(
+        request_lines(&requests),
+        [
+            "GET /api/v1/vector/sites?page=1&per_page=100 HTTP/1.1",
+            "GET /api/v1/vector/environments?site=s1&page=1&per_page=100 HTTP/1.1",
+            "GET /api/v1/vector/environments/e1 HTTP/1.1",
+            "PUT /api/v1/vector/environments/e1 HTTP/1.1",
+        ]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_site_bulk_refuses_without_tty_or_yes() {
+    let (dir, requests) = mock_api("bulk-refuse", vec![]);
+    let output = vector_cmd()
+        .args(["site", "suspend", "--all-sites"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run");
+    assert_eq!(output.status.code(), Some(3)); // EXIT_VALIDATION_ERROR
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--yes"));
+    assert!(request_lines(&requests).is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_site_bulk_with_yes_suspends_selected_sites() {
+    let (dir, requests) = mock_api(
+        "bulk-yes",
+        vec![
+            (
+                200,
+                r#"{"data": [{"id": "s1", "tags": ["legacy"]}, {"id": "s2", "tags": []}]}"#,
+            ),
+            (200, r#"{"data": {}}"#),
+        ],
+    );
+    let output = vector_cmd()
+        .args(["site", "suspend", "--tag", "legacy", "--yes", "--json"])
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    assert_eq!(
+        request_lines(&requests),
+        [
+            "GET /api/v1/vector/sites?page=1&per_page=100 HTTP/1.1",
+            "PUT /api/v1/vector/sites/s1/suspend HTTP/1.1",
+        ]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_site_logs_export_resume_drops_rows_written_after_the_cursor() {
+    let (dir, requests) = mock_api(
+        "export-resume",
+        vec![(
+            200,
+            r#"{"data": {"logs": {"tables": [{"rows": [{"n": 2}, {"n": 3}]}]}, "has_more": false}}"#,
+        )],
+    );
+    let output_path = dir.join("logs.ndjson");
+    // Page two was written, but the process died before saving its cursor.
+    std::fs::write(&output_path, "{\"n\":1}\n{\"n\":2}\n").unwrap();
+    std::fs::write(
+        dir.join("logs.ndjson.cursor"),
+        r#"{"query": {"cursor": "c2"}, "rows": 1, "bytes": 8}"#,
+    )
+    .unwrap();
+
+    let output = vector_cmd()
+        .args(["site", "logs", "export", "s1", "--resume", "--output"])
+        .arg(&output_path)
+        .env("VECTOR_CONFIG_DIR", &dir)
+        .env("VECTOR_API_KEY", "test-key")
+        .output()
+        .expect("Failed to run");
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        "{\"n\":1}\n{\"n\":2}\n{\"n\":3}\n"
+    );
+    assert_eq!(
+        request_lines(&requests),
+        ["GET /api/v1/vector/sites/s1/logs?cursor=c2 HTTP/1.1"]
+    );
+    assert!(!dir.join("logs.ndjson.cursor").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}